@@ -1,6 +1,7 @@
 use green_moon_2d::error::GMError;
 use green_moon_2d::resources::GMResourceManager;
 use green_moon_2d::particle::GMParticleManager;
+use green_moon_2d::input::{GMInput, GMInputMapping, GMInputBinding};
 
 use macroquad::prelude::*;
 
@@ -20,8 +21,13 @@ async fn main() -> Result<(), GMError> {
     let border3 = resources.get_border("border3").unwrap();
     let border4 = resources.get_border("border4").unwrap();
 
+    let mut mapping = GMInputMapping::new_default();
+    mapping.set_bindings("exit", &[GMInputBinding::Key(KeyCode::Escape)]);
+    let mut input = GMInput::new();
+
     loop {
         clear_background(BLACK);
+        input.update(&mapping);
 
         border1.draw();
         border1.update();
@@ -35,7 +41,7 @@ async fn main() -> Result<(), GMError> {
         border4.draw();
         border4.update();
 
-        if is_key_pressed(KeyCode::Escape) {
+        if input.is_pressed("exit") {
             break;
         }
 
@@ -2,6 +2,7 @@ use green_moon_2d::error::GMError;
 use green_moon_2d::resources::GMResourceManager;
 use green_moon_2d::bullets::GMBulletManager;
 use green_moon_2d::utils::GMOffscreenMode;
+use green_moon_2d::input::{GMInput, GMInputMapping, GMInputBinding};
 
 use macroquad::prelude::*;
 
@@ -16,7 +17,7 @@ async fn main() -> Result<(), GMError> {
     let mut bullet_manager = GMBulletManager::new_from_resource(&resources, "bullet1", 5, "laser1");
     bullet_manager.set_delay(0.05);
     //bullet_manager.set_offscreen_mode(GMOffscreenMode::WrapAround);
-    let bullet_speed = 6.0;
+    let bullet_speed = 360.0;
 
     let mut player = resources.get_sprite("ship1").unwrap().clone();
     player.set_x(400.0);
@@ -24,26 +25,33 @@ async fn main() -> Result<(), GMError> {
 
     show_mouse(true);
 
+    let mut mapping = GMInputMapping::new_default();
+    mapping.set_bindings("fire", &[GMInputBinding::Mouse(MouseButton::Left)]);
+    mapping.set_bindings("exit", &[GMInputBinding::Key(KeyCode::Escape)]);
+    let mut input = GMInput::new();
+
     loop {
         clear_background(BLACK);
+        input.update(&mapping);
 
         bullet_manager.draw();
         player.draw();
 
-        bullet_manager.update();
-        player.update();
+        let dt = get_frame_time();
+        bullet_manager.update(dt);
+        player.update(dt);
 
-        let (mousex, mousey) = mouse_position();
+        let (mousex, mousey) = input.get_mouse_position();
         player.rotate_to_point(mousex, mousey);
 
-        if is_mouse_button_pressed(MouseButton::Left) {
+        if input.is_pressed("fire") {
             let rotation = player.get_rotation();
             let bullet_vx = rotation.cos() * bullet_speed;
             let bullet_vy = rotation.sin() * bullet_speed;
             bullet_manager.add_bullet(player.get_mid_x(), player.get_mid_y(), bullet_vx, bullet_vy, rotation, true);
         }
 
-        if is_key_pressed(KeyCode::Escape) {
+        if input.is_pressed("exit") {
             break;
         }
 
@@ -1,4 +1,5 @@
 use green_moon_2d::menu::GMMenu;
+use green_moon_2d::input::{GMInput, GMInputMapping};
 use green_moon_2d::error::GMError;
 use green_moon_2d::resources::GMResourceManager;
 
@@ -23,13 +24,17 @@ async fn main() -> Result<(), GMError> {
 
     show_mouse(true);
 
+    let mapping = GMInputMapping::new_default();
+    let mut input = GMInput::new();
+
     loop {
         clear_background(BLACK);
+        input.update(&mapping);
 
         main_menu.draw();
         main_menu.update();
 
-        if let Some((i, _)) = main_menu.event() {
+        if let Some((i, _)) = main_menu.event(&input) {
             println!("User has selected item: {}", i);
 
             if i == 6 {
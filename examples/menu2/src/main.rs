@@ -1,4 +1,5 @@
 use green_moon_2d::menu::GMMenu;
+use green_moon_2d::input::{GMInput, GMInputMapping};
 use green_moon_2d::menuitem::{GMMenuItemStatic, GMMenuItemEnum, GMMenuItemNumeric};
 use green_moon_2d::utils::{GMValue, GMKeyValue};
 use green_moon_2d::text::GMTextWave;
@@ -59,13 +60,17 @@ async fn main() -> Result<(), GMError> {
     main_menu.add_item(GMMenuItemStatic::new_static_sprite("EXIT", 0.0, 0.0,
         &fonts[0], &sprites[0]), 0.0, 40.0);
 
+    let mapping = GMInputMapping::new_default();
+    let mut input = GMInput::new();
+
     loop {
         clear_background(BLACK);
+        input.update(&mapping);
 
         main_menu.draw();
         main_menu.update();
 
-        match main_menu.event() {
+        match main_menu.event(&input) {
             None => {
                 // Nothing to do...
             }
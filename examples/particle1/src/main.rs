@@ -1,6 +1,7 @@
 use green_moon_2d::error::GMError;
 use green_moon_2d::resources::GMResourceManager;
 use green_moon_2d::particle::GMParticleManager;
+use green_moon_2d::input::{GMInput, GMInputMapping, GMInputBinding};
 
 use macroquad::prelude::*;
 
@@ -14,27 +15,34 @@ async fn main() -> Result<(), GMError> {
 
     let mut emitter1 = GMParticleManager::new_from_resource(&resources, "particle1", 200.0, 300.0);
     emitter1.set_active(true);
-    emitter1.set_speed_max(2.0);
+    emitter1.set_speed_max(120.0);
     emitter1.set_duration(2.0);
-    emitter1.set_rot_speed_max(0.1);
+    emitter1.set_rot_speed_max(6.0);
 
     let mut emitter2 = GMParticleManager::new_from_resource(&resources, "head1", 600.0, 300.0);
     emitter2.set_active(true);
-    emitter2.set_speed_max(2.0);
+    emitter2.set_speed_max(120.0);
     emitter2.set_duration(2.0);
     emitter2.set_delay(0.05);
-    emitter2.set_rot_speed_max(0.2);
+    emitter2.set_rot_speed_max(12.0);
+
+    let mut mapping = GMInputMapping::new_default();
+    mapping.set_bindings("exit", &[GMInputBinding::Key(KeyCode::Escape)]);
+    let mut input = GMInput::new();
 
     loop {
         clear_background(BLACK);
+        input.update(&mapping);
+
+        let dt = get_frame_time();
 
         emitter1.draw();
-        emitter1.update();
+        emitter1.update(dt);
 
         emitter2.draw();
-        emitter2.update();
+        emitter2.update(dt);
 
-        if is_key_pressed(KeyCode::Escape) {
+        if input.is_pressed("exit") {
             break;
         }
 
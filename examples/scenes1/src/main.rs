@@ -1,6 +1,7 @@
 use green_moon_2d::error::GMError;
 use green_moon_2d::resources::GMResourceManager;
 use green_moon_2d::scene::GMSceneManager;
+use green_moon_2d::context::GMContext;
 
 use macroquad::prelude::*;
 
@@ -22,13 +23,13 @@ async fn main() -> Result<(), GMError> {
 
     let resources = GMResourceManager::new_from_file("resources.json").await?;
 
-    let mut scenes_manager = GMSceneManager::new();
-
     let sc1 = scene1::Scene1::new(&resources);
     let sc2 = scene2::Scene2::new(&resources);
     let sc3 = scene3::Scene3::new(&resources);
     let sc4 = scene4::Scene4::new(&resources);
 
+    let mut scenes_manager = GMSceneManager::new(GMContext::new(resources, ()));
+
     scenes_manager.add_scene("scene1", sc1);
     scenes_manager.add_scene("scene2", sc2);
     scenes_manager.add_scene("scene3", sc3);
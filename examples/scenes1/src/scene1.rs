@@ -1,6 +1,9 @@
 use green_moon_2d::menu::GMMenu;
 use green_moon_2d::scene::{GMSceneT, GMSceneResult};
+use green_moon_2d::context::GMContext;
 use green_moon_2d::resources::GMResourceManager;
+use green_moon_2d::transition::GMTransition;
+use green_moon_2d::utils::GMDirection4;
 
 use macroquad::prelude::*;
 
@@ -24,29 +27,30 @@ impl Scene1 {
 }
 
 impl GMSceneT for Scene1 {
-    fn init(&mut self) {
-        println!("Scene 1, init()");
+    fn on_enter(&mut self, _context: &mut GMContext, _from: Option<&str>) {
+        println!("Scene 1, on_enter()");
     }
-    fn draw(&self) {
+    fn draw(&self, _context: &GMContext) {
         clear_background(BLACK);
         self.menu.draw();
     }
-    fn update(&mut self) {
+    fn update(&mut self, _context: &mut GMContext, _dt: f32) {
         self.menu.update();
     }
-    fn event(&mut self) -> GMSceneResult {
-        if let Some((i, _)) = self.menu.event() {
+    fn event(&mut self, context: &mut GMContext) -> GMSceneResult {
+        if let Some((i, _)) = self.menu.event(&context.input) {
             println!("Scene 1, user has selected item: {}", i);
 
             match i {
                 0 => {
-                    GMSceneResult::ChangeScene("scene2".to_string())
+                    context.send_message("scene2", "Hello from scene 1".to_string());
+                    GMSceneResult::ChangeSceneWith("scene2".to_string(), GMTransition::FadeToColor(BLACK, 1.0))
                 }
                 1 => {
-                    GMSceneResult::ChangeScene("scene3".to_string())
+                    GMSceneResult::ChangeSceneWith("scene3".to_string(), GMTransition::Slide(GMDirection4::Left, 0.5))
                 }
                 2 => {
-                    GMSceneResult::ChangeScene("scene4".to_string())
+                    GMSceneResult::ChangeSceneWith("scene4".to_string(), GMTransition::CrossFade(0.5))
                 }
                 _ => {
                     GMSceneResult::Exit
@@ -1,5 +1,6 @@
 use green_moon_2d::menu::GMMenu;
 use green_moon_2d::scene::{GMSceneT, GMSceneResult};
+use green_moon_2d::context::GMContext;
 use green_moon_2d::resources::GMResourceManager;
 
 use macroquad::prelude::*;
@@ -24,18 +25,18 @@ impl Scene3 {
 }
 
 impl GMSceneT for Scene3 {
-    fn init(&mut self) {
-        println!("Scene 3, init()");
+    fn on_enter(&mut self, _context: &mut GMContext, _from: Option<&str>) {
+        println!("Scene 3, on_enter()");
     }
-    fn draw(&self) {
+    fn draw(&self, _context: &GMContext) {
         clear_background(BLACK);
         self.menu.draw();
     }
-    fn update(&mut self) {
+    fn update(&mut self, _context: &mut GMContext, _dt: f32) {
         self.menu.update();
     }
-    fn event(&mut self) -> GMSceneResult {
-        if let Some((i, _)) = self.menu.event() {
+    fn event(&mut self, context: &mut GMContext) -> GMSceneResult {
+        if let Some((i, _)) = self.menu.event(&context.input) {
             println!("Scene 3, user has selected item: {}", i);
 
             match i {
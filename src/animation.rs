@@ -1,5 +1,6 @@
+use crate::clock::GMStartTime;
+
 use macroquad::math::Rect;
-use macroquad::time::get_time;
 
 
 // TODO: Add other animation types
@@ -10,6 +11,8 @@ pub trait GMAnimationT {
     fn resume(&mut self);
     fn next_frame(&mut self);
     fn get_rect(&self) -> Rect;
+    // Rects of all frames, for ex. to build collision masks
+    fn get_frames(&self) -> Vec<Rect>;
     fn finished(&self) -> bool;
     // impl Clone is not possible because of object safety:
     // clone() returns Self
@@ -44,6 +47,9 @@ impl GMAnimation {
     pub fn get_rect(&self) -> Rect {
         self.animation.get_rect()
     }
+    pub fn get_frames(&self) -> Vec<Rect> {
+        self.animation.get_frames()
+    }
     pub fn finished(&self) -> bool {
         self.animation.finished()
     }
@@ -59,7 +65,7 @@ impl Clone for GMAnimation {
 pub struct GMAnimationBase {
     frames: Vec<(Rect, f64)>,
     current_frame: usize,
-    start_time: f64,
+    start_time: GMStartTime,
     active: bool,
 }
 
@@ -68,25 +74,28 @@ impl GMAnimationBase {
         Self {
             frames: frames.to_vec(),
             current_frame: 0,
-            start_time: 0.0,
+            start_time: GMStartTime::new(),
             active: false,
         }
     }
     fn start(&mut self) {
         self.current_frame = 0;
         self.active = true;
-        self.start_time = get_time();
+        self.start_time.restart(0.0);
     }
     fn pause(&mut self) {
         self.active = false;
     }
     fn resume(&mut self) {
         self.active = true;
-        self.start_time = get_time();
+        self.start_time.restart(0.0);
     }
     fn get_rect(&self) -> Rect {
         self.frames[self.current_frame].0
     }
+    fn get_frames(&self) -> Vec<Rect> {
+        self.frames.iter().map(|(rect, _)| *rect).collect()
+    }
     fn check_frame(&mut self) -> bool {
         let duration = self.frames[self.current_frame].1;
 
@@ -102,14 +111,14 @@ impl GMAnimationBase {
             return true
         }
 
-        if get_time() - self.start_time < duration {
+        if self.start_time.get_elapsed() < duration {
             // Time for current frame has not elapsed yet, so nothing to do.
             // (display the same image as before until the frame time has elapsed)
             return true
         }
 
         // Set time for the current animation frame
-        self.start_time = get_time();
+        self.start_time.restart(0.0);
 
         false
     }
@@ -155,6 +164,9 @@ impl GMAnimationT for GMAnimationForwardOnce {
     fn get_rect(&self) -> Rect {
         self.base.get_rect()
     }
+    fn get_frames(&self) -> Vec<Rect> {
+        self.base.get_frames()
+    }
     fn finished(&self) -> bool {
         self.base.current_frame == self.base.frames.len() - 1
     }
@@ -205,6 +217,9 @@ impl GMAnimationT for GMAnimationForwardLoop {
     fn get_rect(&self) -> Rect {
         self.base.get_rect()
     }
+    fn get_frames(&self) -> Vec<Rect> {
+        self.base.get_frames()
+    }
     fn finished(&self) -> bool {
         false
     }
@@ -252,6 +267,9 @@ impl GMAnimationT for GMAnimationBackwardOnce {
     fn get_rect(&self) -> Rect {
         self.base.get_rect()
     }
+    fn get_frames(&self) -> Vec<Rect> {
+        self.base.get_frames()
+    }
     fn finished(&self) -> bool {
         self.base.current_frame == 0
     }
@@ -301,6 +319,9 @@ impl GMAnimationT for GMAnimationBackwardLoop {
     fn get_rect(&self) -> Rect {
         self.base.get_rect()
     }
+    fn get_frames(&self) -> Vec<Rect> {
+        self.base.get_frames()
+    }
     fn finished(&self) -> bool {
         false
     }
@@ -361,6 +382,9 @@ impl GMAnimationT for GMAnimationPingPong {
     fn get_rect(&self) -> Rect {
         self.base.get_rect()
     }
+    fn get_frames(&self) -> Vec<Rect> {
+        self.base.get_frames()
+    }
     fn finished(&self) -> bool {
         false
     }
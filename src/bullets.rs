@@ -1,9 +1,8 @@
 use crate::resources::GMResourceManager;
 use crate::sprite::GMSprite;
 use crate::sound::GMSound;
-use crate::utils::GMOffscreenMode;
-
-use macroquad::time::get_time;
+use crate::utils::{GMOffscreenMode, GMWorldBounds};
+use crate::clock::get_time;
 
 use std::rc::Rc;
 
@@ -19,6 +18,7 @@ pub struct GMBulletManager {
     delay: f64,
     prev_time: f64,
     offscreen_mode: GMOffscreenMode,
+    world_bounds: GMWorldBounds,
     bullets: Vec<GMSprite>,
     shoot_sound: Rc<GMSound>,
 
@@ -32,6 +32,7 @@ impl GMBulletManager {
             delay: 0.5,
             prev_time: 0.0,
             offscreen_mode: GMOffscreenMode::Destroy,
+            world_bounds: GMWorldBounds::Screen,
             bullets: Vec::new(),
             shoot_sound: shoot_sound.clone(),
         }
@@ -48,6 +49,14 @@ impl GMBulletManager {
     pub fn set_offscreen_mode(&mut self, offscreen_mode: GMOffscreenMode) {
         self.offscreen_mode = offscreen_mode;
     }
+    // Also changes the bounds of all existing bullets
+    pub fn set_world_bounds(&mut self, world_bounds: GMWorldBounds) {
+        self.world_bounds = world_bounds;
+
+        for bullet in self.bullets.iter_mut() {
+            bullet.set_world_bounds(world_bounds);
+        }
+    }
     pub fn add_bullet(&mut self, x: f32, y: f32, vx: f32, vy: f32, rotation: f32, mid: bool) {
         // TODO: Reuse inactive bullets
         let current_time = get_time();
@@ -71,6 +80,7 @@ impl GMBulletManager {
             sprite.set_vx(vx);
             sprite.set_vy(vy);
             sprite.set_rotation(rotation);
+            sprite.set_world_bounds(self.world_bounds);
             sprite.start_animation();
             self.bullets.push(sprite);
         }
@@ -80,25 +90,10 @@ impl GMBulletManager {
             bullet.draw();
         }
     }
-    pub fn update(&mut self) {
-        use GMOffscreenMode::*;
-
+    pub fn update(&mut self, dt: f32) {
         for bullet in self.bullets.iter_mut() {
-            bullet.update();
-
-            match self.offscreen_mode {
-                Keep => {
-                    // Nothing to do, just keep moving the bullet...
-                }
-                Destroy => {
-                    if bullet.is_offscreen() {
-                        bullet.set_active(false);
-                    }
-                }
-                WrapAround => {
-                    bullet.wrap_around();
-                }
-            }
+            bullet.update(dt);
+            bullet.apply_offscreen_mode(self.offscreen_mode);
         }
 
         self.bullets.retain(|bullet| bullet.get_active() );
@@ -0,0 +1,191 @@
+use macroquad::time;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+// TODO:
+// - smooth change of time scale
+
+#[derive(Clone)]
+pub enum GMClockSource {
+    // Real time from macroquad
+    System,
+    // Time only moves forward with advance(), useful for tests and replays
+    Manual,
+    // Time is taken from the parent clock, so pausing the parent pauses this clock, too
+    Parent(Rc<GMClock>),
+}
+
+pub struct GMClock {
+    source: GMClockSource,
+    manual_time: Cell<f64>,
+    last_source_time: Cell<f64>,
+    current_time: Cell<f64>,
+    paused: Cell<bool>,
+    time_scale: Cell<f64>,
+}
+
+impl GMClock {
+    pub fn new() -> Self {
+        Self::new_with_source(GMClockSource::System)
+    }
+    pub fn new_manual() -> Self {
+        Self::new_with_source(GMClockSource::Manual)
+    }
+    pub fn new_child(parent: &Rc<GMClock>) -> Self {
+        Self::new_with_source(GMClockSource::Parent(parent.clone()))
+    }
+    pub fn new_with_source(source: GMClockSource) -> Self {
+        let clock = Self {
+            source,
+            manual_time: Cell::new(0.0),
+            last_source_time: Cell::new(0.0),
+            current_time: Cell::new(0.0),
+            paused: Cell::new(false),
+            time_scale: Cell::new(1.0),
+        };
+
+        clock.last_source_time.set(clock.source_time());
+        clock
+    }
+    fn source_time(&self) -> f64 {
+        match &self.source {
+            GMClockSource::System => time::get_time(),
+            GMClockSource::Manual => self.manual_time.get(),
+            GMClockSource::Parent(parent) => parent.get_time(),
+        }
+    }
+    // Current time of this clock in seconds, starts with 0.0
+    pub fn get_time(&self) -> f64 {
+        let source_time = self.source_time();
+        let delta = source_time - self.last_source_time.get();
+        self.last_source_time.set(source_time);
+
+        if !self.paused.get() {
+            self.current_time.set(self.current_time.get() + (delta * self.time_scale.get()));
+        }
+
+        self.current_time.get()
+    }
+    // Only has an effect for manual clocks
+    pub fn advance(&self, delta: f64) {
+        self.manual_time.set(self.manual_time.get() + delta);
+    }
+    pub fn pause(&self) {
+        // Take the time that has elapsed until now into account
+        self.get_time();
+        self.paused.set(true);
+    }
+    pub fn resume(&self) {
+        // The time while paused must not count
+        self.get_time();
+        self.paused.set(false);
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+    pub fn set_time_scale(&self, time_scale: f64) {
+        self.get_time();
+        self.time_scale.set(time_scale);
+    }
+    pub fn get_time_scale(&self) -> f64 {
+        self.time_scale.get()
+    }
+    // Scale of this clock only, 0.0 if paused
+    pub fn get_local_scale(&self) -> f64 {
+        if self.paused.get() {
+            0.0
+        } else {
+            self.time_scale.get()
+        }
+    }
+    // Scale of this clock including all parent clocks
+    pub fn get_effective_scale(&self) -> f64 {
+        match &self.source {
+            GMClockSource::Parent(parent) => self.get_local_scale() * parent.get_effective_scale(),
+            _ => self.get_local_scale(),
+        }
+    }
+}
+
+impl Default for GMClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static ACTIVE_CLOCK: RefCell<Option<Rc<GMClock>>> = const { RefCell::new(None) };
+}
+
+// All time dependent code in this crate (animations, particles, bullets, ...) uses the active clock.
+// The scene manager sets the clock of the scene that is currently processed.
+// Before the loop runs (in main() or in the constructor of a scene) the game clock of the scene manager is active.
+// Animations and timers started under one clock and checked under another one start again from the
+// current time of the new clock, see GMStartTime.
+pub fn set_active_clock(clock: &Rc<GMClock>) {
+    ACTIVE_CLOCK.with(|active| *active.borrow_mut() = Some(clock.clone()));
+}
+
+// Use the system time again
+pub fn reset_active_clock() {
+    ACTIVE_CLOCK.with(|active| *active.borrow_mut() = None);
+}
+
+pub fn get_active_clock() -> Option<Rc<GMClock>> {
+    ACTIVE_CLOCK.with(|active| active.borrow().clone())
+}
+
+// Replacement for macroquad::time::get_time()
+pub fn get_time() -> f64 {
+    ACTIVE_CLOCK.with(|active| {
+        match &*active.borrow() {
+            Some(clock) => clock.get_time(),
+            None => time::get_time(),
+        }
+    })
+}
+
+// Current time of the active clock and an id for that clock, 0 is the system time
+fn active_time() -> (f64, usize) {
+    ACTIVE_CLOCK.with(|active| {
+        match &*active.borrow() {
+            Some(clock) => (clock.get_time(), Rc::as_ptr(clock) as usize),
+            None => (time::get_time(), 0),
+        }
+    })
+}
+
+// Start time of an animation or a timer together with the clock it was taken from.
+// If it is used under another clock, it is moved to the current time of that clock first,
+// so the time in between does not count.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct GMStartTime {
+    time: Cell<f64>,
+    clock_id: Cell<usize>,
+}
+
+impl GMStartTime {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+    // Starts now, minus the time that has already elapsed
+    pub(crate) fn restart(&self, elapsed: f64) {
+        let (time, clock_id) = active_time();
+        self.time.set(time - elapsed);
+        self.clock_id.set(clock_id);
+    }
+    pub(crate) fn get_elapsed(&self) -> f64 {
+        let (time, clock_id) = active_time();
+
+        if clock_id != self.clock_id.get() {
+            self.time.set(time);
+            self.clock_id.set(clock_id);
+        }
+
+        time - self.time.get()
+    }
+    pub(crate) fn advance(&self, delta: f64) {
+        self.time.set(self.time.get() + delta);
+    }
+}
@@ -0,0 +1,430 @@
+use macroquad::math::Rect;
+use macroquad::texture::Image;
+
+use std::rc::Rc;
+
+// TODO:
+// - concave polygons (split into convex parts)
+
+// Collision shape in world coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub enum GMShape {
+    // Axis aligned box
+    Rectangle(Rect),
+    // Mid point x, y and radius
+    Circle(f32, f32, f32),
+    // Convex polygon, the winding order does not matter
+    Polygon(Vec<(f32, f32)>),
+    // Pixel mask at position x, y with flip_x and flip_y, masks can not be rotated
+    Mask(Rc<GMMask>, f32, f32, bool, bool),
+    // Made of several shapes, for ex. the parts of GMSpriteMultiple
+    Compound(Vec<GMShape>),
+}
+
+impl GMShape {
+    // Box rotated around the given pivot point, rotation is in radians like in GMSpriteT
+    pub fn new_rotated_rect(rect: &Rect, pivot_x: f32, pivot_y: f32, rotation: f32) -> Self {
+        if rotation == 0.0 {
+            return Self::Rectangle(*rect)
+        }
+
+        let points = rect_points(rect).iter()
+            .map(|(x, y)| rotate_point(*x, *y, pivot_x, pivot_y, rotation))
+            .collect();
+
+        Self::Polygon(points)
+    }
+    // Axis aligned box that contains the whole shape
+    pub fn get_bounds(&self) -> Rect {
+        match self {
+            Self::Rectangle(rect) => *rect,
+            Self::Circle(x, y, radius) => Rect::new(x - radius, y - radius, radius * 2.0, radius * 2.0),
+            Self::Polygon(points) => {
+                let mut x1 = f32::MAX;
+                let mut y1 = f32::MAX;
+                let mut x2 = f32::MIN;
+                let mut y2 = f32::MIN;
+
+                for (x, y) in points.iter() {
+                    x1 = x1.min(*x);
+                    y1 = y1.min(*y);
+                    x2 = x2.max(*x);
+                    y2 = y2.max(*y);
+                }
+
+                Rect::new(x1, y1, x2 - x1, y2 - y1)
+            }
+            Self::Mask(mask, x, y, _, _) => Rect::new(*x, *y, mask.get_width() as f32, mask.get_height() as f32),
+            Self::Compound(shapes) => {
+                shapes.iter().map(|shape| shape.get_bounds())
+                    .reduce(|a, b| a.combine_with(b))
+                    .unwrap_or_else(|| Rect::new(0.0, 0.0, 0.0, 0.0))
+            }
+        }
+    }
+    pub fn contains_point(&self, px: f32, py: f32) -> bool {
+        match self {
+            Self::Rectangle(rect) => {
+                rect.x <= px && px <= rect.x + rect.w && rect.y <= py && py <= rect.y + rect.h
+            }
+            Self::Circle(x, y, radius) => {
+                (px - x).hypot(py - y) <= *radius
+            }
+            Self::Polygon(points) => {
+                let point = [(px, py)];
+
+                axes(points).iter().all(|axis| {
+                    let (min, max) = project(points, *axis);
+                    let (p, _) = project(&point, *axis);
+                    min <= p && p <= max
+                })
+            }
+            Self::Mask(mask, x, y, flip_x, flip_y) => {
+                let lx = (px - x).floor();
+                let ly = (py - y).floor();
+
+                if lx < 0.0 || ly < 0.0 {
+                    return false
+                }
+
+                let (mut lx, mut ly) = (lx as usize, ly as usize);
+
+                if lx >= mask.get_width() || ly >= mask.get_height() {
+                    return false
+                }
+
+                if *flip_x {
+                    lx = mask.get_width() - 1 - lx;
+                }
+                if *flip_y {
+                    ly = mask.get_height() - 1 - ly;
+                }
+
+                mask.get(lx, ly)
+            }
+            Self::Compound(shapes) => {
+                shapes.iter().any(|shape| shape.contains_point(px, py))
+            }
+        }
+    }
+    pub fn collides_with(&self, other: &GMShape) -> bool {
+        match (self, other) {
+            (Self::Rectangle(a), Self::Rectangle(b)) => {
+                // No need for SAT here
+                a.x <= b.x + b.w && b.x <= a.x + a.w && a.y <= b.y + b.h && b.y <= a.y + a.h
+            }
+            (Self::Compound(shapes), _) => shapes.iter().any(|shape| shape.collides_with(other)),
+            (_, Self::Compound(shapes)) => shapes.iter().any(|shape| self.collides_with(shape)),
+            _ => self.contact_with(other).is_some(),
+        }
+    }
+    // Returns None if the shapes do not overlap
+    pub fn contact_with(&self, other: &GMShape) -> Option<GMContact> {
+        use GMShape::*;
+
+        match (self, other) {
+            // The part with the deepest penetration gives the contact
+            (Compound(shapes), _) => {
+                shapes.iter().filter_map(|shape| shape.contact_with(other))
+                    .max_by(|a, b| a.penetration.total_cmp(&b.penetration))
+            }
+            (_, Compound(shapes)) => {
+                shapes.iter().filter_map(|shape| self.contact_with(shape))
+                    .max_by(|a, b| a.penetration.total_cmp(&b.penetration))
+            }
+            (Mask(..), _) => {
+                mask_contact(self, other)
+            }
+            (_, Mask(..)) => {
+                mask_contact(other, self).map(|contact| contact.inverse())
+            }
+            (Circle(x1, y1, r1), Circle(x2, y2, r2)) => {
+                circle_circle(*x1, *y1, *r1, *x2, *y2, *r2)
+            }
+            (Circle(x, y, radius), _) => {
+                circle_polygon(*x, *y, *radius, &other.to_points())
+            }
+            (_, Circle(x, y, radius)) => {
+                circle_polygon(*x, *y, *radius, &self.to_points()).map(|contact| contact.inverse())
+            }
+            _ => {
+                polygon_polygon(&self.to_points(), &other.to_points())
+            }
+        }
+    }
+    fn to_points(&self) -> Vec<(f32, f32)> {
+        match self {
+            Self::Rectangle(rect) => rect_points(rect).to_vec(),
+            Self::Circle(x, y, _) => vec![(*x, *y)],
+            Self::Polygon(points) => points.clone(),
+            Self::Mask(..) | Self::Compound(..) => rect_points(&self.get_bounds()).to_vec(),
+        }
+    }
+}
+
+// How two shapes overlap.
+// The normal points from the first shape to the second one, so moving the first shape by
+// -normal * penetration (or the second one by +normal * penetration) separates them.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GMContact {
+    pub normal_x: f32,
+    pub normal_y: f32,
+    pub penetration: f32,
+}
+
+impl GMContact {
+    pub fn inverse(&self) -> Self {
+        Self {
+            normal_x: -self.normal_x,
+            normal_y: -self.normal_y,
+            penetration: self.penetration,
+        }
+    }
+}
+
+// One bit per pixel, set for solid pixels
+#[derive(Clone, Debug, PartialEq)]
+pub struct GMMask {
+    width: usize,
+    height: usize,
+    bits: Vec<u64>,
+}
+
+impl GMMask {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            bits: vec![0; (width * height).div_ceil(64)],
+        }
+    }
+    // All pixels inside the rect with an alpha value above the threshold (0.0 - 1.0) are solid
+    pub fn from_image(image: &Image, rect: &Rect, alpha_threshold: f32) -> Self {
+        let mut mask = Self::new(rect.w.max(0.0) as usize, rect.h.max(0.0) as usize);
+        let x0 = rect.x.max(0.0) as usize;
+        let y0 = rect.y.max(0.0) as usize;
+
+        for y in 0..mask.height {
+            for x in 0..mask.width {
+                let ix = x0 + x;
+                let iy = y0 + y;
+
+                if ix < image.width() && iy < image.height() && image.get_pixel(ix as u32, iy as u32).a > alpha_threshold {
+                    mask.set(x, y, true);
+                }
+            }
+        }
+
+        mask
+    }
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false
+        }
+
+        let i = (y * self.width) + x;
+        (self.bits[i / 64] & (1 << (i % 64))) != 0
+    }
+    pub fn set(&mut self, x: usize, y: usize, solid: bool) {
+        if x >= self.width || y >= self.height {
+            return
+        }
+
+        let i = (y * self.width) + x;
+
+        if solid {
+            self.bits[i / 64] |= 1 << (i % 64);
+        } else {
+            self.bits[i / 64] &= !(1 << (i % 64));
+        }
+    }
+}
+
+pub fn rotate_point(x: f32, y: f32, pivot_x: f32, pivot_y: f32, rotation: f32) -> (f32, f32) {
+    let (sin, cos) = rotation.sin_cos();
+    let dx = x - pivot_x;
+    let dy = y - pivot_y;
+
+    (pivot_x + (dx * cos) - (dy * sin), pivot_y + (dx * sin) + (dy * cos))
+}
+
+fn rect_points(rect: &Rect) -> [(f32, f32); 4] {
+    [
+        (rect.x, rect.y),
+        (rect.x + rect.w, rect.y),
+        (rect.x + rect.w, rect.y + rect.h),
+        (rect.x, rect.y + rect.h),
+    ]
+}
+
+fn center(points: &[(f32, f32)]) -> (f32, f32) {
+    let (sx, sy) = points.iter().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    let n = points.len().max(1) as f32;
+    (sx / n, sy / n)
+}
+
+// Unit normals of all edges
+fn axes(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut result = Vec::with_capacity(points.len());
+
+    for (i, (x1, y1)) in points.iter().enumerate() {
+        let (x2, y2) = points[(i + 1) % points.len()];
+        let nx = -(y2 - y1);
+        let ny = x2 - x1;
+        let length = nx.hypot(ny);
+
+        if length > f32::EPSILON {
+            result.push((nx / length, ny / length));
+        }
+    }
+
+    result
+}
+
+fn project(points: &[(f32, f32)], (ax, ay): (f32, f32)) -> (f32, f32) {
+    points.iter().fold((f32::MAX, f32::MIN), |(min, max), (x, y)| {
+        let p = (x * ax) + (y * ay);
+        (min.min(p), max.max(p))
+    })
+}
+
+// Separating axis theorem: the shapes overlap if their projections overlap on every axis.
+// The axis with the smallest overlap gives the contact normal.
+fn smallest_overlap(a: &[(f32, f32)], b: &[(f32, f32)], axes: &[(f32, f32)],
+    project_a: impl Fn((f32, f32)) -> (f32, f32)) -> Option<((f32, f32), f32)> {
+    let mut result: Option<((f32, f32), f32)> = None;
+
+    for axis in axes.iter() {
+        let (a_min, a_max) = project_a(*axis);
+        let (b_min, b_max) = project(b, *axis);
+        let overlap = a_max.min(b_max) - a_min.max(b_min);
+
+        if overlap < 0.0 {
+            return None
+        }
+
+        match result {
+            Some((_, penetration)) if penetration <= overlap => {}
+            _ => result = Some((*axis, overlap)),
+        }
+    }
+
+    // Let the normal point from a to b
+    result.map(|((nx, ny), penetration)| {
+        let (ax, ay) = center(a);
+        let (bx, by) = center(b);
+
+        if ((bx - ax) * nx) + ((by - ay) * ny) < 0.0 {
+            ((-nx, -ny), penetration)
+        } else {
+            ((nx, ny), penetration)
+        }
+    })
+}
+
+fn polygon_polygon(a: &[(f32, f32)], b: &[(f32, f32)]) -> Option<GMContact> {
+    let mut all_axes = axes(a);
+    all_axes.extend(axes(b));
+
+    smallest_overlap(a, b, &all_axes, |axis| project(a, axis)).map(|((normal_x, normal_y), penetration)| {
+        GMContact { normal_x, normal_y, penetration }
+    })
+}
+
+fn circle_circle(x1: f32, y1: f32, r1: f32, x2: f32, y2: f32, r2: f32) -> Option<GMContact> {
+    let dx = x2 - x1;
+    let dy = y2 - y1;
+    let distance = dx.hypot(dy);
+
+    if distance > r1 + r2 {
+        return None
+    }
+
+    // Same mid point: any direction will do
+    let (normal_x, normal_y) = if distance > f32::EPSILON { (dx / distance, dy / distance) } else { (1.0, 0.0) };
+
+    Some(GMContact {
+        normal_x,
+        normal_y,
+        penetration: r1 + r2 - distance,
+    })
+}
+
+fn circle_polygon(x: f32, y: f32, radius: f32, points: &[(f32, f32)]) -> Option<GMContact> {
+    let mut all_axes = axes(points);
+
+    // The axis from the closest corner to the circle is needed for collisions at the corners
+    let closest = points.iter().min_by(|(x1, y1), (x2, y2)| {
+        (x1 - x).hypot(y1 - y).total_cmp(&(x2 - x).hypot(y2 - y))
+    });
+
+    if let Some((px, py)) = closest {
+        let dx = px - x;
+        let dy = py - y;
+        let length = dx.hypot(dy);
+
+        if length > f32::EPSILON {
+            all_axes.push((dx / length, dy / length));
+        }
+    }
+
+    let circle = [(x, y)];
+    let project_circle = |(ax, ay): (f32, f32)| {
+        let p = (x * ax) + (y * ay);
+        (p - radius, p + radius)
+    };
+
+    smallest_overlap(&circle, points, &all_axes, project_circle).map(|((normal_x, normal_y), penetration)| {
+        GMContact { normal_x, normal_y, penetration }
+    })
+}
+
+// Checks every pixel of the mask that lies inside the other shape.
+// The contact is only an approximation: the normal points from the middle of the first shape
+// to the middle of the second one and the penetration is the smaller side of the overlapping area.
+fn mask_contact(mask: &GMShape, other: &GMShape) -> Option<GMContact> {
+    let a = mask.get_bounds();
+    let b = other.get_bounds();
+    let overlap = a.intersect(b)?;
+
+    let mut x1 = f32::MAX;
+    let mut y1 = f32::MAX;
+    let mut x2 = f32::MIN;
+    let mut y2 = f32::MIN;
+
+    // Pixels are tested at their mid point
+    for y in (overlap.y - a.y).floor() as i32..(overlap.bottom() - a.y).ceil() as i32 {
+        for x in (overlap.x - a.x).floor() as i32..(overlap.right() - a.x).ceil() as i32 {
+            let px = a.x + x as f32 + 0.5;
+            let py = a.y + y as f32 + 0.5;
+
+            if mask.contains_point(px, py) && other.contains_point(px, py) {
+                x1 = x1.min(px);
+                y1 = y1.min(py);
+                x2 = x2.max(px);
+                y2 = y2.max(py);
+            }
+        }
+    }
+
+    if x1 > x2 {
+        return None
+    }
+
+    let dx = b.center().x - a.center().x;
+    let dy = b.center().y - a.center().y;
+    let length = dx.hypot(dy);
+    let (normal_x, normal_y) = if length > f32::EPSILON { (dx / length, dy / length) } else { (1.0, 0.0) };
+
+    Some(GMContact {
+        normal_x,
+        normal_y,
+        penetration: (x2 - x1 + 1.0).min(y2 - y1 + 1.0),
+    })
+}
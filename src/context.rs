@@ -0,0 +1,65 @@
+use crate::resources::GMResourceManager;
+use crate::option::GMOption;
+use crate::score::{GMScoreTable, GMScoreTables};
+use crate::input::GMInput;
+use crate::gamepad::GMGamepad;
+
+use std::any::Any;
+use std::collections::HashMap;
+
+// Shared data for all scenes, owned by the scene manager.
+// T is for user defined game data.
+pub struct GMContext<T = ()> {
+    pub resources: GMResourceManager,
+    pub options: GMOption,
+    // Contains one table named "default" with 10 entries
+    pub score_tables: GMScoreTables,
+    // Updated by the scene manager at the beginning of every frame
+    pub input: GMInput,
+    pub gamepad: GMGamepad,
+    pub data: T,
+    // Messages for a scene, key is the name of the receiving scene
+    messages: HashMap<String, Vec<Box<dyn Any>>>,
+    // Interpolation value between the last two fixed updates, see GMLoopMode
+    alpha: f32,
+}
+
+impl<T> GMContext<T> {
+    pub fn new(resources: GMResourceManager, data: T) -> Self {
+        let mut score_tables = GMScoreTables::new();
+        score_tables.add_table("default", GMScoreTable::new(10));
+
+        Self {
+            resources,
+            options: GMOption::new(),
+            score_tables,
+            input: GMInput::new(),
+            gamepad: GMGamepad::default(),
+            data,
+            messages: HashMap::new(),
+            alpha: 1.0,
+        }
+    }
+    pub fn send_message<M: 'static>(&mut self, scene: &str, message: M) {
+        self.messages.entry(scene.to_string()).or_default().push(Box::new(message));
+    }
+    // Returns the oldest message of type M for the given scene and removes it
+    pub fn take_message<M: 'static>(&mut self, scene: &str) -> Option<M> {
+        let messages = self.messages.get_mut(scene)?;
+        let i = messages.iter().position(|m| m.is::<M>())?;
+
+        messages.remove(i).downcast::<M>().ok().map(|m| *m)
+    }
+    pub fn has_message(&self, scene: &str) -> bool {
+        self.messages.get(scene).is_some_and(|m| !m.is_empty())
+    }
+    pub fn clear_messages(&mut self, scene: &str) {
+        self.messages.remove(scene);
+    }
+    pub fn get_alpha(&self) -> f32 {
+        self.alpha
+    }
+    pub(crate) fn set_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha;
+    }
+}
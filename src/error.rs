@@ -9,5 +9,11 @@ pub enum GMError {
     #[error("IO error while loading file")]
     FileError(#[from] FileError),
     #[error("Could not deserialize JSON string")]
-    JSONError(#[from] DeJsonErr)
+    JSONError(#[from] DeJsonErr),
+    #[error("IO error while saving file")]
+    IOError(#[from] std::io::Error),
+    #[error("Unsupported file version: {0}")]
+    VersionError(u32),
+    #[error("Checksum does not match, file has been modified")]
+    ChecksumError,
 }
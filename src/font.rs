@@ -14,6 +14,10 @@ use macroquad::math::Rect;
 pub trait GMFontT {
     fn draw(&self, c: char, x: f32, y: f32);
     fn get_extend(&self, c: char) -> (f32, f32);
+    // Can this character be drawn ?
+    fn has_char(&self, _c: char) -> bool {
+        true
+    }
 }
 
 #[derive(Clone)]
@@ -33,6 +37,31 @@ impl GMFont {
     pub fn get_extend(&self, c: char) -> (f32, f32) {
         self.font.get_extend(c)
     }
+    pub fn has_char(&self, c: char) -> bool {
+        self.font.has_char(c)
+    }
+    // Characters that the font can not draw are skipped
+    pub fn draw_text(&self, text: &str, x: f32, y: f32) {
+        let mut current_x = x;
+
+        for c in text.chars().filter(|c| self.has_char(*c)) {
+            self.draw(c, current_x, y);
+            let (char_width, _) = self.get_extend(c);
+            current_x += char_width;
+        }
+    }
+    pub fn get_text_extend(&self, text: &str) -> (f32, f32) {
+        let mut text_width: f32 = 0.0;
+        let mut text_height: f32 = 0.0;
+
+        for c in text.chars().filter(|c| self.has_char(*c)) {
+            let (char_width, char_height) = self.get_extend(c);
+            text_width += char_width;
+            text_height = text_height.max(char_height);
+        }
+
+        (text_width, text_height)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -93,4 +122,7 @@ impl GMFontT for GMBitmapFont {
         let rect = self.mapping[&c];
         (rect.w, rect.h)
     }
+    fn has_char(&self, c: char) -> bool {
+        self.mapping.contains_key(&c)
+    }
 }
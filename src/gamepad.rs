@@ -0,0 +1,291 @@
+use crate::input::{GMInput, GMGamepadButton};
+use crate::utils::GMDirection4;
+
+use log::info;
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+// TODO:
+// - record axis values in replays
+// - rumble
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GMGamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GMGamepadStick {
+    Left,
+    Right,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GMGamepadEvent {
+    Connected(usize),
+    Disconnected(usize),
+}
+
+// macroquad can not read gamepads, so the actual reading is done by a backend (for ex. using gilrs).
+// Stick axes are in the range -1.0 to 1.0, positive y is down (like screen coordinates).
+// Triggers are in the range 0.0 to 1.0.
+pub trait GMGamepadBackendT {
+    // Returns all connect / disconnect events since the last call
+    fn poll(&mut self) -> Vec<GMGamepadEvent>;
+    fn is_button_down(&self, id: usize, button: GMGamepadButton) -> bool;
+    fn get_axis(&self, id: usize, axis: GMGamepadAxis) -> f32;
+}
+
+// Used when no gamepad backend is available
+pub struct GMGamepadBackendNone;
+
+impl GMGamepadBackendT for GMGamepadBackendNone {
+    fn poll(&mut self) -> Vec<GMGamepadEvent> {
+        Vec::new()
+    }
+    fn is_button_down(&self, _id: usize, _button: GMGamepadButton) -> bool {
+        false
+    }
+    fn get_axis(&self, _id: usize, _axis: GMGamepadAxis) -> f32 {
+        0.0
+    }
+}
+
+#[derive(Default)]
+struct GMFakeState {
+    events: Vec<GMGamepadEvent>,
+    buttons: HashSet<(usize, GMGamepadButton)>,
+    axes: HashMap<(usize, GMGamepadAxis), f32>,
+}
+
+// Backend for tests: all clones share the same state,
+// so the state can be changed after the backend has been given to GMGamepad.
+#[derive(Clone, Default)]
+pub struct GMGamepadBackendFake {
+    state: Rc<RefCell<GMFakeState>>,
+}
+
+impl GMGamepadBackendFake {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn connect(&self, id: usize) {
+        self.state.borrow_mut().events.push(GMGamepadEvent::Connected(id));
+    }
+    pub fn disconnect(&self, id: usize) {
+        let mut state = self.state.borrow_mut();
+        state.events.push(GMGamepadEvent::Disconnected(id));
+        state.buttons.retain(|(pad, _)| *pad != id);
+        state.axes.retain(|(pad, _), _| *pad != id);
+    }
+    pub fn set_button(&self, id: usize, button: GMGamepadButton, down: bool) {
+        let mut state = self.state.borrow_mut();
+
+        if down {
+            state.buttons.insert((id, button));
+        } else {
+            state.buttons.remove(&(id, button));
+        }
+    }
+    pub fn set_axis(&self, id: usize, axis: GMGamepadAxis, value: f32) {
+        self.state.borrow_mut().axes.insert((id, axis), value);
+    }
+}
+
+impl GMGamepadBackendT for GMGamepadBackendFake {
+    fn poll(&mut self) -> Vec<GMGamepadEvent> {
+        self.state.borrow_mut().events.drain(..).collect()
+    }
+    fn is_button_down(&self, id: usize, button: GMGamepadButton) -> bool {
+        self.state.borrow().buttons.contains(&(id, button))
+    }
+    fn get_axis(&self, id: usize, axis: GMGamepadAxis) -> f32 {
+        self.state.borrow().axes.get(&(id, axis)).copied().unwrap_or(0.0)
+    }
+}
+
+// Values inside the dead zone are 0.0, the rest is rescaled to the full range
+pub fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let abs_value = value.abs();
+
+    if abs_value <= dead_zone || dead_zone >= 1.0 {
+        0.0
+    } else {
+        value.signum() * ((abs_value.min(1.0) - dead_zone) / (1.0 - dead_zone))
+    }
+}
+
+// Dead zone is applied to the length of the vector, so diagonals are not cut off
+pub fn stick_to_vector(x: f32, y: f32, dead_zone: f32) -> (f32, f32) {
+    let length = (x * x + y * y).sqrt();
+
+    if length <= dead_zone {
+        return (0.0, 0.0)
+    }
+
+    let scaled = apply_dead_zone(length, dead_zone);
+    (x / length * scaled, y / length * scaled)
+}
+
+// The axis with the larger value wins
+pub fn stick_to_direction4(x: f32, y: f32, dead_zone: f32) -> Option<GMDirection4> {
+    let (x, y) = stick_to_vector(x, y, dead_zone);
+
+    if x == 0.0 && y == 0.0 {
+        None
+    } else if x.abs() > y.abs() {
+        if x > 0.0 { Some(GMDirection4::Right) } else { Some(GMDirection4::Left) }
+    } else if y > 0.0 {
+        Some(GMDirection4::Down)
+    } else {
+        Some(GMDirection4::Up)
+    }
+}
+
+const ALL_BUTTONS: [GMGamepadButton; 16] = [
+    GMGamepadButton::A, GMGamepadButton::B, GMGamepadButton::X, GMGamepadButton::Y,
+    GMGamepadButton::LeftShoulder, GMGamepadButton::RightShoulder,
+    GMGamepadButton::LeftTrigger, GMGamepadButton::RightTrigger,
+    GMGamepadButton::Back, GMGamepadButton::Start,
+    GMGamepadButton::LeftStick, GMGamepadButton::RightStick,
+    GMGamepadButton::DPadUp, GMGamepadButton::DPadDown, GMGamepadButton::DPadLeft, GMGamepadButton::DPadRight,
+];
+
+pub struct GMGamepad {
+    backend: Box<dyn GMGamepadBackendT>,
+    dead_zone: f32,
+    // Connected gamepads in the order they were connected
+    connected: Vec<usize>,
+    // Events of the last update
+    events: Vec<GMGamepadEvent>,
+    // The left stick also triggers the d-pad buttons, useful for menus
+    stick_as_dpad: bool,
+}
+
+impl GMGamepad {
+    pub fn new<T: 'static + GMGamepadBackendT>(backend: T) -> Self {
+        Self {
+            backend: Box::new(backend),
+            dead_zone: 0.2,
+            connected: Vec::new(),
+            events: Vec::new(),
+            stick_as_dpad: true,
+        }
+    }
+    pub fn set_backend<T: 'static + GMGamepadBackendT>(&mut self, backend: T) {
+        self.backend = Box::new(backend);
+        self.connected.clear();
+    }
+    pub fn set_dead_zone(&mut self, dead_zone: f32) {
+        self.dead_zone = dead_zone;
+    }
+    pub fn get_dead_zone(&self) -> f32 {
+        self.dead_zone
+    }
+    pub fn set_stick_as_dpad(&mut self, stick_as_dpad: bool) {
+        self.stick_as_dpad = stick_as_dpad;
+    }
+    // Call this once per frame, the scene manager does this automatically
+    pub fn update(&mut self) {
+        self.events = self.backend.poll();
+
+        for event in self.events.iter() {
+            match event {
+                GMGamepadEvent::Connected(id) => {
+                    info!("GMGamepad::update(), gamepad connected: {}", id);
+                    if !self.connected.contains(id) {
+                        self.connected.push(*id);
+                    }
+                }
+                GMGamepadEvent::Disconnected(id) => {
+                    info!("GMGamepad::update(), gamepad disconnected: {}", id);
+                    self.connected.retain(|c| c != id);
+                }
+            }
+        }
+    }
+    pub fn get_events(&self) -> &[GMGamepadEvent] {
+        &self.events
+    }
+    pub fn get_connected(&self) -> &[usize] {
+        &self.connected
+    }
+    pub fn is_connected(&self, id: usize) -> bool {
+        self.connected.contains(&id)
+    }
+    // The first connected gamepad controls the menus.
+    // If it is disconnected the next one takes over.
+    pub fn get_main_gamepad(&self) -> Option<usize> {
+        self.connected.first().copied()
+    }
+    pub fn is_button_down(&self, id: usize, button: GMGamepadButton) -> bool {
+        self.is_connected(id) && self.backend.is_button_down(id, button)
+    }
+    // Dead zone is already applied
+    pub fn get_axis(&self, id: usize, axis: GMGamepadAxis) -> f32 {
+        if !self.is_connected(id) {
+            return 0.0
+        }
+
+        apply_dead_zone(self.backend.get_axis(id, axis), self.dead_zone)
+    }
+    fn get_raw_stick(&self, id: usize, stick: GMGamepadStick) -> (f32, f32) {
+        if !self.is_connected(id) {
+            return (0.0, 0.0)
+        }
+
+        let (axis_x, axis_y) = match stick {
+            GMGamepadStick::Left => (GMGamepadAxis::LeftX, GMGamepadAxis::LeftY),
+            GMGamepadStick::Right => (GMGamepadAxis::RightX, GMGamepadAxis::RightY),
+        };
+
+        (self.backend.get_axis(id, axis_x), self.backend.get_axis(id, axis_y))
+    }
+    // Length of the vector is between 0.0 and 1.0
+    pub fn get_stick(&self, id: usize, stick: GMGamepadStick) -> (f32, f32) {
+        let (x, y) = self.get_raw_stick(id, stick);
+        stick_to_vector(x, y, self.dead_zone)
+    }
+    pub fn get_stick_direction4(&self, id: usize, stick: GMGamepadStick) -> Option<GMDirection4> {
+        let (x, y) = self.get_raw_stick(id, stick);
+        stick_to_direction4(x, y, self.dead_zone)
+    }
+    // Passes the buttons of the main gamepad to the input layer, so they can be used in an input mapping
+    pub fn apply_to_input(&self, input: &mut GMInput) {
+        let main = self.get_main_gamepad();
+        let stick_direction = match main {
+            Some(id) if self.stick_as_dpad => self.get_stick_direction4(id, GMGamepadStick::Left),
+            _ => None,
+        };
+
+        for button in ALL_BUTTONS.iter() {
+            let mut down = match main {
+                Some(id) => self.is_button_down(id, *button),
+                None => false,
+            };
+
+            if let Some(direction) = stick_direction {
+                down = down || matches!((button, direction),
+                    (GMGamepadButton::DPadUp, GMDirection4::Up) |
+                    (GMGamepadButton::DPadDown, GMDirection4::Down) |
+                    (GMGamepadButton::DPadLeft, GMDirection4::Left) |
+                    (GMGamepadButton::DPadRight, GMDirection4::Right));
+            }
+
+            input.set_gamepad_button(*button, down);
+        }
+    }
+}
+
+impl Default for GMGamepad {
+    fn default() -> Self {
+        Self::new(GMGamepadBackendNone)
+    }
+}
@@ -0,0 +1,354 @@
+use macroquad::input::{KeyCode, MouseButton, is_key_down, is_key_pressed, is_key_released,
+    is_mouse_button_down, is_mouse_button_pressed, is_mouse_button_released, mouse_position, get_char_pressed,
+    get_last_key_pressed};
+
+use nanoserde::{DeJson, SerJson};
+
+use std::collections::{HashMap, HashSet};
+
+// TODO:
+// - key combinations (ctrl + s)
+// - mouse wheel
+
+// Actions used by the menu system
+pub const MENU_UP: &str = "menu_up";
+pub const MENU_DOWN: &str = "menu_down";
+pub const MENU_LEFT: &str = "menu_left";
+pub const MENU_RIGHT: &str = "menu_right";
+pub const MENU_SELECT: &str = "menu_select";
+// Delete the last character in a text input
+pub const MENU_BACKSPACE: &str = "menu_backspace";
+// Primary and secondary mouse click on a menu item
+pub const MENU_CLICK: &str = "menu_click";
+pub const MENU_ALT_CLICK: &str = "menu_alt_click";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GMGamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    LeftShoulder,
+    RightShoulder,
+    LeftTrigger,
+    RightTrigger,
+    Back,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+// Generates the conversion from and to names, used for storing the mapping in a file
+macro_rules! name_conversion {
+    ($to_name:ident, $from_name:ident, $type:ident, $($variant:ident),+) => {
+        pub fn $to_name(value: $type) -> &'static str {
+            match value {
+                $($type::$variant => stringify!($variant),)+
+            }
+        }
+        pub fn $from_name(name: &str) -> Option<$type> {
+            match name {
+                $(stringify!($variant) => Some($type::$variant),)+
+                _ => None,
+            }
+        }
+    };
+}
+
+name_conversion!(key_to_name, key_from_name, KeyCode,
+    Space, Apostrophe, Comma, Minus, Period, Slash,
+    Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+    Semicolon, Equal,
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    LeftBracket, Backslash, RightBracket, GraveAccent, World1, World2,
+    Escape, Enter, Tab, Backspace, Insert, Delete, Right, Left, Down, Up,
+    PageUp, PageDown, Home, End, CapsLock, ScrollLock, NumLock, PrintScreen, Pause,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, F13, F14, F15, F16, F17, F18, F19, F20,
+    F21, F22, F23, F24, F25,
+    Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9,
+    KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual,
+    LeftShift, LeftControl, LeftAlt, LeftSuper, RightShift, RightControl, RightAlt, RightSuper,
+    Menu, Unknown);
+
+name_conversion!(mouse_to_name, mouse_from_name, MouseButton,
+    Left, Right, Middle, Unknown);
+
+name_conversion!(gamepad_to_name, gamepad_from_name, GMGamepadButton,
+    A, B, X, Y, LeftShoulder, RightShoulder, LeftTrigger, RightTrigger, Back, Start,
+    LeftStick, RightStick, DPadUp, DPadDown, DPadLeft, DPadRight);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GMInputBinding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    GamepadButton(GMGamepadButton),
+}
+
+impl GMInputBinding {
+    // Name is for ex. "key:Up", "mouse:Left" or "gamepad:A"
+    pub fn to_name(&self) -> String {
+        match self {
+            GMInputBinding::Key(key) => format!("key:{}", key_to_name(*key)),
+            GMInputBinding::Mouse(button) => format!("mouse:{}", mouse_to_name(*button)),
+            GMInputBinding::GamepadButton(button) => format!("gamepad:{}", gamepad_to_name(*button)),
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (kind, value) = name.split_once(':')?;
+
+        match kind {
+            "key" => key_from_name(value).map(GMInputBinding::Key),
+            "mouse" => mouse_from_name(value).map(GMInputBinding::Mouse),
+            "gamepad" => gamepad_from_name(value).map(GMInputBinding::GamepadButton),
+            _ => None,
+        }
+    }
+}
+
+// Maps named actions ("menu_up", "fire", "jump", ...) to keys and buttons
+#[derive(Clone, Debug, PartialEq)]
+pub struct GMInputMapping {
+    actions: HashMap<String, Vec<GMInputBinding>>,
+}
+
+impl GMInputMapping {
+    pub fn new() -> Self {
+        Self {
+            actions: HashMap::new(),
+        }
+    }
+    // Contains all actions needed by the menu system
+    pub fn new_default() -> Self {
+        use GMInputBinding::*;
+
+        let mut mapping = Self::new();
+
+        mapping.set_bindings(MENU_UP, &[Key(KeyCode::Up), GamepadButton(GMGamepadButton::DPadUp)]);
+        mapping.set_bindings(MENU_DOWN, &[Key(KeyCode::Down), GamepadButton(GMGamepadButton::DPadDown)]);
+        mapping.set_bindings(MENU_LEFT, &[Key(KeyCode::Left), GamepadButton(GMGamepadButton::DPadLeft)]);
+        mapping.set_bindings(MENU_RIGHT, &[Key(KeyCode::Right), GamepadButton(GMGamepadButton::DPadRight)]);
+        mapping.set_bindings(MENU_SELECT, &[Key(KeyCode::Enter), Key(KeyCode::KpEnter), GamepadButton(GMGamepadButton::A)]);
+        mapping.set_bindings(MENU_BACKSPACE, &[Key(KeyCode::Backspace)]);
+        mapping.set_bindings(MENU_CLICK, &[Mouse(MouseButton::Left)]);
+        mapping.set_bindings(MENU_ALT_CLICK, &[Mouse(MouseButton::Right)]);
+
+        mapping
+    }
+    pub fn add_binding(&mut self, action: &str, binding: GMInputBinding) {
+        let bindings = self.actions.entry(action.to_string()).or_default();
+
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+    pub fn remove_binding(&mut self, action: &str, binding: GMInputBinding) {
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.retain(|b| *b != binding);
+        }
+    }
+    pub fn set_bindings(&mut self, action: &str, bindings: &[GMInputBinding]) {
+        self.actions.insert(action.to_string(), bindings.to_vec());
+    }
+    pub fn get_bindings(&self, action: &str) -> &[GMInputBinding] {
+        match self.actions.get(action) {
+            Some(bindings) => bindings,
+            None => &[],
+        }
+    }
+    pub fn remove_action(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+    pub fn get_actions(&self) -> Vec<&str> {
+        self.actions.keys().map(|a| a.as_str()).collect()
+    }
+    // Returns all actions that use the given binding, useful to detect conflicts
+    pub fn find_actions(&self, binding: GMInputBinding) -> Vec<&str> {
+        self.actions.iter()
+            .filter(|(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| action.as_str())
+            .collect()
+    }
+}
+
+impl Default for GMInputMapping {
+    fn default() -> Self {
+        Self::new_default()
+    }
+}
+
+// Snapshot of the input state of one frame, used for recording and replay
+#[derive(Clone, Debug, Default, PartialEq, DeJson, SerJson)]
+pub struct GMInputFrame {
+    pub held: Vec<String>,
+    pub pressed: Vec<String>,
+    pub released: Vec<String>,
+    pub mouse_x: f32,
+    pub mouse_y: f32,
+    // Characters typed in this frame
+    #[nserde(default)]
+    pub text: String,
+    // Name of the last key pressed in this frame, empty if there was none
+    #[nserde(default)]
+    pub last_key: String,
+}
+
+// State of all actions for the current frame.
+// Call update() once at the beginning of every frame, the scene manager does this automatically.
+pub struct GMInput {
+    held: HashSet<String>,
+    pressed: HashSet<String>,
+    released: HashSet<String>,
+    mouse_x: f32,
+    mouse_y: f32,
+    text: String,
+    last_key: Option<KeyCode>,
+    gamepad_buttons: HashSet<GMGamepadButton>,
+    prev_gamepad_buttons: HashSet<GMGamepadButton>,
+}
+
+impl GMInput {
+    pub fn new() -> Self {
+        Self {
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+            released: HashSet::new(),
+            mouse_x: 0.0,
+            mouse_y: 0.0,
+            text: String::new(),
+            last_key: None,
+            gamepad_buttons: HashSet::new(),
+            prev_gamepad_buttons: HashSet::new(),
+        }
+    }
+    fn binding_state(&self, binding: &GMInputBinding) -> (bool, bool, bool) {
+        match binding {
+            GMInputBinding::Key(key) => {
+                (is_key_down(*key), is_key_pressed(*key), is_key_released(*key))
+            }
+            GMInputBinding::Mouse(button) => {
+                (is_mouse_button_down(*button), is_mouse_button_pressed(*button), is_mouse_button_released(*button))
+            }
+            GMInputBinding::GamepadButton(button) => {
+                let down = self.gamepad_buttons.contains(button);
+                let prev_down = self.prev_gamepad_buttons.contains(button);
+                (down, down && !prev_down, !down && prev_down)
+            }
+        }
+    }
+    pub fn update(&mut self, mapping: &GMInputMapping) {
+        self.held.clear();
+        self.pressed.clear();
+        self.released.clear();
+
+        for (action, bindings) in mapping.actions.iter() {
+            let mut held = false;
+            let mut pressed = false;
+            let mut released = false;
+
+            for binding in bindings.iter() {
+                let (h, p, r) = self.binding_state(binding);
+                held = held || h;
+                pressed = pressed || p;
+                released = released || r;
+            }
+
+            if held {
+                self.held.insert(action.clone());
+            }
+            if pressed {
+                self.pressed.insert(action.clone());
+            }
+            // Only released if no other binding still holds the action
+            if released && !held {
+                self.released.insert(action.clone());
+            }
+        }
+
+        let (mouse_x, mouse_y) = mouse_position();
+        self.mouse_x = mouse_x;
+        self.mouse_y = mouse_y;
+
+        self.last_key = get_last_key_pressed();
+
+        self.text.clear();
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() {
+                self.text.push(c);
+            }
+        }
+
+        self.prev_gamepad_buttons = self.gamepad_buttons.clone();
+    }
+    // True only in the frame where the action was triggered
+    pub fn is_pressed(&self, action: &str) -> bool {
+        self.pressed.contains(action)
+    }
+    // True as long as one of the bindings is down
+    pub fn is_held(&self, action: &str) -> bool {
+        self.held.contains(action)
+    }
+    // True only in the frame where the last binding was let go
+    pub fn is_released(&self, action: &str) -> bool {
+        self.released.contains(action)
+    }
+    pub fn get_frame(&self) -> GMInputFrame {
+        // Sorted, so that the same state always gives the same frame
+        fn sorted(actions: &HashSet<String>) -> Vec<String> {
+            let mut result: Vec<String> = actions.iter().cloned().collect();
+            result.sort();
+            result
+        }
+
+        GMInputFrame {
+            held: sorted(&self.held),
+            pressed: sorted(&self.pressed),
+            released: sorted(&self.released),
+            mouse_x: self.mouse_x,
+            mouse_y: self.mouse_y,
+            text: self.text.clone(),
+            last_key: self.last_key.map(key_to_name).unwrap_or_default().to_string(),
+        }
+    }
+    // Use this instead of update() to replay a recorded frame
+    pub fn set_frame(&mut self, frame: &GMInputFrame) {
+        self.held = frame.held.iter().cloned().collect();
+        self.pressed = frame.pressed.iter().cloned().collect();
+        self.released = frame.released.iter().cloned().collect();
+        self.mouse_x = frame.mouse_x;
+        self.mouse_y = frame.mouse_y;
+        self.text = frame.text.clone();
+        self.last_key = key_from_name(&frame.last_key);
+
+        // Characters typed during the replay are thrown away, otherwise they show up after the replay has finished
+        while get_char_pressed().is_some() {}
+    }
+    // All characters typed since the last update
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+    // Useful for changing key bindings
+    pub fn get_last_key(&self) -> Option<KeyCode> {
+        self.last_key
+    }
+    pub fn get_mouse_position(&self) -> (f32, f32) {
+        (self.mouse_x, self.mouse_y)
+    }
+    // There is no gamepad support in macroquad, so the state has to be set from outside (see GMGamepad)
+    pub fn set_gamepad_button(&mut self, button: GMGamepadButton, down: bool) {
+        if down {
+            self.gamepad_buttons.insert(button);
+        } else {
+            self.gamepad_buttons.remove(&button);
+        }
+    }
+}
+
+impl Default for GMInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,146 @@
+// Movement of a sprite, all values are in units per second
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GMKinematics {
+    vx: f32,
+    vy: f32,
+    ax: f32,
+    ay: f32,
+    gravity_x: f32,
+    gravity_y: f32,
+    // Drag, the velocity shrinks by the factor e^-friction per second, 0.0 means no friction
+    friction: f32,
+    max_speed: Option<f32>,
+    rot_speed: f32,
+    rot_acceleration: f32,
+    max_rot_speed: Option<f32>,
+}
+
+impl GMKinematics {
+    pub fn new() -> Self {
+        Self {
+            vx: 0.0,
+            vy: 0.0,
+            ax: 0.0,
+            ay: 0.0,
+            gravity_x: 0.0,
+            gravity_y: 0.0,
+            friction: 0.0,
+            max_speed: None,
+            rot_speed: 0.0,
+            rot_acceleration: 0.0,
+            max_rot_speed: None,
+        }
+    }
+    pub fn set_velocity(&mut self, vx: f32, vy: f32) {
+        self.vx = vx;
+        self.vy = vy;
+    }
+    pub fn get_velocity(&self) -> (f32, f32) {
+        (self.vx, self.vy)
+    }
+    // Angle in radians, like the rotation of a sprite
+    pub fn set_velocity_polar(&mut self, speed: f32, angle: f32) {
+        let (sin, cos) = angle.sin_cos();
+        self.vx = speed * cos;
+        self.vy = speed * sin;
+    }
+    pub fn get_speed(&self) -> f32 {
+        self.vx.hypot(self.vy)
+    }
+    pub fn set_vx(&mut self, vx: f32) {
+        self.vx = vx;
+    }
+    pub fn set_vy(&mut self, vy: f32) {
+        self.vy = vy;
+    }
+    // Changes the velocity immediately, for ex. for a jump or an explosion
+    pub fn apply_impulse(&mut self, ix: f32, iy: f32) {
+        self.vx += ix;
+        self.vy += iy;
+    }
+    pub fn set_acceleration(&mut self, ax: f32, ay: f32) {
+        self.ax = ax;
+        self.ay = ay;
+    }
+    pub fn get_acceleration(&self) -> (f32, f32) {
+        (self.ax, self.ay)
+    }
+    pub fn set_acceleration_polar(&mut self, acceleration: f32, angle: f32) {
+        let (sin, cos) = angle.sin_cos();
+        self.ax = acceleration * cos;
+        self.ay = acceleration * sin;
+    }
+    pub fn set_gravity(&mut self, gravity_x: f32, gravity_y: f32) {
+        self.gravity_x = gravity_x;
+        self.gravity_y = gravity_y;
+    }
+    pub fn get_gravity(&self) -> (f32, f32) {
+        (self.gravity_x, self.gravity_y)
+    }
+    pub fn set_friction(&mut self, friction: f32) {
+        self.friction = friction;
+    }
+    pub fn get_friction(&self) -> f32 {
+        self.friction
+    }
+    pub fn set_max_speed(&mut self, max_speed: Option<f32>) {
+        self.max_speed = max_speed;
+    }
+    pub fn get_max_speed(&self) -> Option<f32> {
+        self.max_speed
+    }
+    pub fn set_rot_speed(&mut self, rot_speed: f32) {
+        self.rot_speed = rot_speed;
+    }
+    pub fn get_rot_speed(&self) -> f32 {
+        self.rot_speed
+    }
+    pub fn set_rot_acceleration(&mut self, rot_acceleration: f32) {
+        self.rot_acceleration = rot_acceleration;
+    }
+    pub fn get_rot_acceleration(&self) -> f32 {
+        self.rot_acceleration
+    }
+    pub fn set_max_rot_speed(&mut self, max_rot_speed: Option<f32>) {
+        self.max_rot_speed = max_rot_speed;
+    }
+    pub fn get_max_rot_speed(&self) -> Option<f32> {
+        self.max_rot_speed
+    }
+    // Returns the change of x, y and rotation for this time step
+    pub fn update(&mut self, dt: f32) -> (f32, f32, f32) {
+        self.vx += (self.ax + self.gravity_x) * dt;
+        self.vy += (self.ay + self.gravity_y) * dt;
+
+        if self.friction > 0.0 {
+            // Does not depend on the frame rate
+            let factor = (-self.friction * dt).exp();
+            self.vx *= factor;
+            self.vy *= factor;
+        }
+
+        if let Some(max_speed) = self.max_speed {
+            let speed = self.get_speed();
+
+            if speed > max_speed {
+                self.vx *= max_speed / speed;
+                self.vy *= max_speed / speed;
+            }
+        }
+
+        self.rot_speed += self.rot_acceleration * dt;
+
+        if let Some(max_rot_speed) = self.max_rot_speed {
+            let max_rot_speed = max_rot_speed.abs();
+            self.rot_speed = self.rot_speed.clamp(-max_rot_speed, max_rot_speed);
+        }
+
+        (self.vx * dt, self.vy * dt, self.rot_speed * dt)
+    }
+}
+
+impl Default for GMKinematics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,22 +2,36 @@ pub mod animation;
 pub mod behavior;
 pub mod border;
 pub mod bullets;
+pub mod clock;
+pub mod collision;
+pub mod context;
 pub mod error;
 pub mod font;
+pub mod gamepad;
 pub mod healthbar;
+pub mod input;
+pub mod kinematics;
+pub mod localization;
 pub mod menuitem;
 pub mod menu;
 pub mod option;
+pub mod optionmenu;
 pub mod particle;
 pub mod person;
+pub mod replay;
 pub mod resources;
 pub mod scene;
 pub mod score;
+pub mod scorescene;
 pub mod sound;
 pub mod sprite;
 pub mod spritesheet;
+pub mod spritestate;
 pub mod text;
 pub mod tilemap;
 pub mod tileset;
 pub mod tilewindow;
+pub mod timer;
+pub mod transition;
+pub mod tween;
 pub mod utils;
@@ -0,0 +1,225 @@
+use crate::error::GMError;
+use crate::text::{GMText, GMTextT, GMTextStatic};
+use crate::font::GMFont;
+use crate::utils::GMKeyValue;
+
+use macroquad::file::load_string;
+use nanoserde::DeJson;
+
+use log::{info, error};
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// TODO:
+// - plural forms
+
+#[derive(Clone, Debug, Default, DeJson)]
+pub struct GMFormatStringTable {
+    language: String,
+    strings: HashMap<String, String>,
+}
+
+// String tables for all languages. Shared with Rc, so the language can be changed for all texts at once.
+pub struct GMLocalization {
+    // language -> (key -> string)
+    tables: RefCell<HashMap<String, HashMap<String, String>>>,
+    language: RefCell<String>,
+    // Used if a key is missing in the current language
+    fallback: RefCell<String>,
+    // Increased on every change, so that texts know when they have to be updated
+    generation: Cell<usize>,
+}
+
+impl GMLocalization {
+    pub fn new(language: &str, fallback: &str) -> Self {
+        Self {
+            tables: RefCell::new(HashMap::new()),
+            language: RefCell::new(language.to_string()),
+            fallback: RefCell::new(fallback.to_string()),
+            generation: Cell::new(0),
+        }
+    }
+    fn changed(&self) {
+        self.generation.set(self.generation.get() + 1);
+    }
+    // Strings are added to an existing table of the same language
+    pub fn add_table(&self, language: &str, strings: HashMap<String, String>) {
+        self.tables.borrow_mut().entry(language.to_string()).or_default().extend(strings);
+        self.changed();
+    }
+    pub async fn table_from_file(&self, file_name: &str) -> Result<(), GMError> {
+        info!("Loading string table file: '{}'", file_name);
+        let json = load_string(file_name).await?;
+        let result: GMFormatStringTable = DeJson::deserialize_json(&json)?;
+        self.add_table(&result.language, result.strings);
+        Ok(())
+    }
+    pub fn set_language(&self, language: &str) {
+        if *self.language.borrow() != language {
+            *self.language.borrow_mut() = language.to_string();
+            self.changed();
+        }
+    }
+    pub fn get_language(&self) -> String {
+        self.language.borrow().clone()
+    }
+    pub fn set_fallback(&self, fallback: &str) {
+        *self.fallback.borrow_mut() = fallback.to_string();
+        self.changed();
+    }
+    pub fn get_fallback(&self) -> String {
+        self.fallback.borrow().clone()
+    }
+    pub fn get_generation(&self) -> usize {
+        self.generation.get()
+    }
+    fn lookup(&self, language: &str, key: &str) -> Option<String> {
+        self.tables.borrow().get(language).and_then(|table| table.get(key)).cloned()
+    }
+    pub fn has_key(&self, key: &str) -> bool {
+        self.lookup(&self.language.borrow(), key).is_some()
+    }
+    // Returns the key itself if it is neither in the current nor in the fallback language
+    pub fn get(&self, key: &str) -> String {
+        if let Some(value) = self.lookup(&self.language.borrow(), key) {
+            return value
+        }
+
+        if let Some(value) = self.lookup(&self.fallback.borrow(), key) {
+            return value
+        }
+
+        error!("GMLocalization::get(), unknown key: '{}'", key);
+        key.to_string()
+    }
+    // Replaces placeholders: format("score_text", &[("score", "100")]) turns "Score: {score}" into "Score: 100"
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let mut result = self.get(key);
+
+        for (name, value) in args.iter() {
+            result = result.replace(&format!("{{{}}}", name), value);
+        }
+
+        result
+    }
+}
+
+impl Default for GMLocalization {
+    fn default() -> Self {
+        Self::new("eng", "eng")
+    }
+}
+
+// Wraps any other text and sets its text from the string table.
+// The text is updated in update() when the language changes.
+pub struct GMTextLocalized {
+    base: GMText,
+    localization: Rc<GMLocalization>,
+    key: String,
+    args: Vec<(String, String)>,
+    generation: usize,
+}
+
+impl GMTextLocalized {
+    pub fn new(base: GMText, key: &str, localization: &Rc<GMLocalization>) -> Self {
+        let mut result = Self {
+            base,
+            localization: localization.clone(),
+            key: key.to_string(),
+            args: Vec::new(),
+            generation: localization.get_generation(),
+        };
+
+        result.update_text();
+        result
+    }
+    pub fn new_box(base: GMText, key: &str, localization: &Rc<GMLocalization>) -> GMText {
+        GMText::new(Self::new(base, key, localization))
+    }
+    // The base text is only used for position, font and effects, the text comes from the string table
+    pub fn new_static(key: &str, x: f32, y: f32, font: &GMFont, localization: &Rc<GMLocalization>) -> GMText {
+        let base = GMTextStatic::new_box("", x, y, font);
+        Self::new_box(base, key, localization)
+    }
+    fn update_text(&mut self) {
+        let args: Vec<(&str, &str)> = self.args.iter().map(|(n, v)| (n.as_str(), v.as_str())).collect();
+        let text = self.localization.format(&self.key, &args);
+        self.base.set_text(&text);
+        self.generation = self.localization.get_generation();
+    }
+    pub fn set_key(&mut self, key: &str) {
+        self.key = key.to_string();
+        self.update_text();
+    }
+    pub fn get_key(&self) -> &str {
+        &self.key
+    }
+    // Value for a placeholder in the string
+    pub fn set_arg(&mut self, name: &str, value: &str) {
+        match self.args.iter_mut().find(|(n, _)| n == name) {
+            Some(arg) => arg.1 = value.to_string(),
+            None => self.args.push((name.to_string(), value.to_string())),
+        }
+        self.update_text();
+    }
+}
+
+impl GMTextT for GMTextLocalized {
+    fn draw(&self) {
+        self.base.draw();
+    }
+    fn update(&mut self) {
+        if self.generation != self.localization.get_generation() {
+            self.update_text();
+        }
+        self.base.update();
+    }
+    // The given text is used as the new key
+    fn set_text(&mut self, text: &str) {
+        self.set_key(text);
+    }
+    fn get_text(&self) -> &str {
+        self.base.get_text()
+    }
+    fn set_x(&mut self, x: f32) {
+        self.base.set_x(x);
+    }
+    fn get_x(&self) -> f32 {
+        self.base.get_x()
+    }
+    fn set_y(&mut self, y: f32) {
+        self.base.set_y(y);
+    }
+    fn get_y(&self) -> f32 {
+        self.base.get_y()
+    }
+    fn set_font(&mut self, font: &GMFont) {
+        self.base.set_font(font);
+    }
+    fn get_font(&self) -> &GMFont {
+        self.base.get_font()
+    }
+    fn from_other(&mut self, other: &GMText) {
+        self.base.from_other(other);
+        self.update_text();
+    }
+    fn get_extend(&self) -> (f32, f32) {
+        self.base.get_extend()
+    }
+    fn set_property(&mut self, data: &GMKeyValue) {
+        if data.key == "arg" {
+            match data.value.downcast_ref::<(String, String)>() {
+                Some((name, value)) => {
+                    self.set_arg(name, value);
+                }
+                None => {
+                    error!("GMTextLocalized::set_property(), could not downcast value to (String, String)")
+                }
+            }
+        } else {
+            self.base.set_property(data)
+        }
+    }
+}
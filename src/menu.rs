@@ -5,6 +5,7 @@ use crate::sprite::GMSpriteSimple;
 use crate::sound::GMSound;
 use crate::menuitem::{GMMenuItem, GMMenuItemStatic, GMMenuItemEvent};
 use crate::utils::{GMKeyValue, GMValue};
+use crate::input::GMInput;
 
 // use macroquad::window::{screen_width};
 
@@ -98,13 +99,13 @@ impl GMMenu {
             item.update();
         }
     }
-    pub fn event(&mut self) -> Option<(usize, GMValue)> {
+    pub fn event(&mut self, input: &GMInput) -> Option<(usize, GMValue)> {
         let first = 0;
         let last = self.items.len() - 1;
         let mut new_highlighted: Option<(usize, bool)> = None;
 
         for (i, item) in self.items.iter_mut().enumerate() {
-            match item.event() {
+            match item.event(input) {
                 Some(e) => {
                     use GMMenuItemEvent::*;
                     use GMValue::*;
@@ -165,6 +166,12 @@ impl GMMenu {
 
         None
     }
+    pub fn set_item_text(&mut self, i: usize, text: &str) {
+        self.items[i].set_text(text);
+    }
+    pub fn get_num_of_items(&self) -> usize {
+        self.items.len()
+    }
     pub fn set_title_font(&mut self, font: &GMFont) {
         self.title.set_font(font);
     }
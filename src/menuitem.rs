@@ -4,8 +4,11 @@ use crate::text::{GMText, GMTextStatic, GMTextArrow, GMTextSprite};
 use crate::sprite::GMSpriteSimple;
 use crate::resources::GMResourceManager;
 use crate::utils::{GMKeyValue, in_rect, GMValue};
+use crate::input::{GMInput, MENU_UP, MENU_DOWN, MENU_LEFT, MENU_RIGHT, MENU_SELECT, MENU_BACKSPACE, MENU_CLICK, MENU_ALT_CLICK};
+use crate::timer::GMTimer;
+use crate::localization::{GMLocalization, GMTextLocalized};
 
-use macroquad::input::{is_key_pressed, KeyCode, mouse_position, is_mouse_button_pressed, MouseButton};
+use std::rc::Rc;
 
 
 // TODO:
@@ -29,7 +32,7 @@ pub trait GMMenuItemT {
     fn get_x(&self) -> f32;
     fn set_y(&mut self, y: f32);
     fn get_y(&self) -> f32;
-    fn event(&mut self) -> Option<GMMenuItemEvent>;
+    fn event(&mut self, input: &GMInput) -> Option<GMMenuItemEvent>;
     fn set_font(&mut self, font: &GMFont);
     fn set_property(&mut self, data: &GMKeyValue);
 }
@@ -71,8 +74,8 @@ impl GMMenuItem {
     pub fn get_y(&self) -> f32 {
         self.menu_item.get_y()
     }
-    pub fn event(&mut self) -> Option<GMMenuItemEvent> {
-        self.menu_item.event()
+    pub fn event(&mut self, input: &GMInput) -> Option<GMMenuItemEvent> {
+        self.menu_item.event(input)
     }
     pub fn set_font(&mut self, font: &GMFont) {
         self.menu_item.set_font(font);
@@ -114,6 +117,17 @@ impl GMMenuItemStatic {
         let sprite = resources.get_sprite_simple(sprite_name).unwrap();
         Self::new_static_sprite(text, x, y, &font, &sprite)
     }
+    // The text is taken from the string table and changes with the language
+    pub fn new_localized_arrow(key: &str, x: f32, y: f32, font: &GMFont, localization: &Rc<GMLocalization>) -> GMMenuItem {
+        let inactive_text = GMTextLocalized::new_box(GMTextStatic::new_box(key, x, y, font), key, localization);
+        let active_text = GMTextLocalized::new_box(GMTextArrow::new_static(key, x, y, font), key, localization);
+        Self::new_box(inactive_text, active_text)
+    }
+    pub fn new_localized_sprite(key: &str, x: f32, y: f32, font: &GMFont, sprite: &GMSpriteSimple, localization: &Rc<GMLocalization>) -> GMMenuItem {
+        let inactive_text = GMTextLocalized::new_box(GMTextStatic::new_box(key, x, y, font), key, localization);
+        let active_text = GMTextLocalized::new_box(GMTextSprite::new_static(key, x, y, font, sprite), key, localization);
+        Self::new_box(inactive_text, active_text)
+    }
     pub fn get_extend(&self) -> (f32, f32) {
         self.inactive_text.get_extend()
     }
@@ -141,6 +155,7 @@ impl GMMenuItemT for GMMenuItemStatic {
         }
     }
     fn update(&mut self) {
+        // A localized text picks up a language change in its own update(), before it is drawn again
         if self.active {
             self.active_text.update();
         } else {
@@ -153,20 +168,20 @@ impl GMMenuItemT for GMMenuItemStatic {
     fn get_active(&self) -> bool {
         self.active
     }
-    fn event(&mut self) -> Option<GMMenuItemEvent> {
+    fn event(&mut self, input: &GMInput) -> Option<GMMenuItemEvent> {
         use GMMenuItemEvent::*;
 
-        let (mousex, mousey) = mouse_position();
+        let (mousex, mousey) = input.get_mouse_position();
         let point_inside = self.point_inside(mousex, mousey);
 
         if self.active {
-            if is_key_pressed(KeyCode::Up) {
+            if input.is_pressed(MENU_UP) {
                 self.active = false;
                 Some(HighlightPrevItem)
-            } else if is_key_pressed(KeyCode::Down) {
+            } else if input.is_pressed(MENU_DOWN) {
                 self.active = false;
                 Some(HighlightNextItem)
-            } else if is_key_pressed(KeyCode::Enter) || (is_mouse_button_pressed(MouseButton::Left) && point_inside) {
+            } else if input.is_pressed(MENU_SELECT) || (input.is_pressed(MENU_CLICK) && point_inside) {
                 Some(SelectThisItem)
             } else {
                 None
@@ -264,27 +279,27 @@ impl GMMenuItemT for GMMenuItemNumeric {
     fn get_active(&self) -> bool {
         self.base.get_active()
     }
-    fn event(&mut self) -> Option<GMMenuItemEvent> {
+    fn event(&mut self, input: &GMInput) -> Option<GMMenuItemEvent> {
         use GMMenuItemEvent::*;
 
-        let (mousex, mousey) = mouse_position();
+        let (mousex, mousey) = input.get_mouse_position();
         let point_inside = self.base.point_inside(mousex, mousey);
 
         if self.base.get_active() {
-            if is_key_pressed(KeyCode::Up) {
+            if input.is_pressed(MENU_UP) {
                 self.base.set_active(false);
                 Some(HighlightPrevItem)
-            } else if is_key_pressed(KeyCode::Down) {
+            } else if input.is_pressed(MENU_DOWN) {
                 self.base.set_active(false);
                 Some(HighlightNextItem)
-            } else if is_key_pressed(KeyCode::Left) || (is_mouse_button_pressed(MouseButton::Left) && point_inside) {
+            } else if input.is_pressed(MENU_LEFT) || (input.is_pressed(MENU_CLICK) && point_inside) {
                 self.current_val -= self.step;
                 if self.current_val < self.min_val {
                     self.current_val = self.min_val
                 }
                 self.update_text();
                 Some(NewValue(GMValue::F32(self.current_val)))
-            } else if is_key_pressed(KeyCode::Right) || (is_mouse_button_pressed(MouseButton::Right) && point_inside) {
+            } else if input.is_pressed(MENU_RIGHT) || (input.is_pressed(MENU_ALT_CLICK) && point_inside) {
                 self.current_val += self.step;
                 if self.current_val > self.max_val {
                     self.current_val = self.max_val
@@ -386,23 +401,23 @@ impl GMMenuItemT for GMMenuItemEnum {
     fn get_active(&self) -> bool {
         self.base.get_active()
     }
-    fn event(&mut self) -> Option<GMMenuItemEvent> {
+    fn event(&mut self, input: &GMInput) -> Option<GMMenuItemEvent> {
         use GMMenuItemEvent::*;
 
-        let (mousex, mousey) = mouse_position();
+        let (mousex, mousey) = input.get_mouse_position();
         let point_inside = self.base.point_inside(mousex, mousey);
 
         if self.base.get_active() {
             let first = 0;
             let last = self.items.len() - 1;
 
-            if is_key_pressed(KeyCode::Up) {
+            if input.is_pressed(MENU_UP) {
                 self.base.set_active(false);
                 Some(HighlightPrevItem)
-            } else if is_key_pressed(KeyCode::Down) {
+            } else if input.is_pressed(MENU_DOWN) {
                 self.base.set_active(false);
                 Some(HighlightNextItem)
-            } else if is_key_pressed(KeyCode::Left) || (is_mouse_button_pressed(MouseButton::Left) && point_inside) {
+            } else if input.is_pressed(MENU_LEFT) || (input.is_pressed(MENU_CLICK) && point_inside) {
                 if self.current_item > first {
                     self.current_item -= 1;
                 } else {
@@ -410,7 +425,7 @@ impl GMMenuItemT for GMMenuItemEnum {
                 }
                 self.update_text();
                 Some(NewValue(GMValue::USize(self.current_item)))
-            } else if is_key_pressed(KeyCode::Right) || (is_mouse_button_pressed(MouseButton::Right) && point_inside) {
+            } else if input.is_pressed(MENU_RIGHT) || (input.is_pressed(MENU_ALT_CLICK) && point_inside) {
                 if self.current_item < last {
                     self.current_item += 1;
                 } else {
@@ -449,3 +464,165 @@ impl GMMenuItemT for GMMenuItemEnum {
         self.base.get_y()
     }
 }
+
+pub struct GMMenuItemTextInput {
+    base: GMMenuItemStatic,
+    prefix: String,
+    value: String,
+    max_length: usize,
+    // Used to check which characters can be typed
+    font: GMFont,
+    cursor: char,
+    cursor_visible: bool,
+    cursor_timer: GMTimer,
+}
+
+impl GMMenuItemTextInput {
+    pub fn new(inactive_text: GMText, active_text: GMText, prefix: &str, value: &str, max_length: usize) -> Self {
+        let font = inactive_text.get_font().clone();
+        let base = GMMenuItemStatic::new(inactive_text, active_text);
+        let mut cursor_timer = GMTimer::new_repeat(0.5);
+        cursor_timer.start();
+
+        let mut result = Self {
+            base,
+            prefix: prefix.to_string(),
+            value: value.chars().take(max_length).collect(),
+            max_length,
+            font,
+            cursor: '_',
+            cursor_visible: true,
+            cursor_timer,
+        };
+
+        result.update_text();
+        result
+    }
+    pub fn new_box(inactive_text: GMText, active_text: GMText, prefix: &str, value: &str, max_length: usize) -> GMMenuItem {
+        GMMenuItem::new(Self::new(inactive_text, active_text, prefix, value, max_length))
+    }
+    pub fn new_static_arrow(prefix: &str, x: f32, y: f32, font: &GMFont, value: &str, max_length: usize) -> GMMenuItem {
+        let inactive_text = GMTextStatic::new_box(prefix, x, y, font);
+        let active_text = GMTextArrow::new_static(prefix, x, y, font);
+        Self::new_box(inactive_text, active_text, prefix, value, max_length)
+    }
+    pub fn new_static_sprite(prefix: &str, x: f32, y: f32, font: &GMFont, sprite: &GMSpriteSimple, value: &str, max_length: usize) -> GMMenuItem {
+        let inactive_text = GMTextStatic::new_box(prefix, x, y, font);
+        let active_text = GMTextSprite::new_static(prefix, x, y, font, sprite);
+        Self::new_box(inactive_text, active_text, prefix, value, max_length)
+    }
+    pub fn update_text(&mut self) {
+        // The cursor is only shown if the font can draw it
+        let text = if self.base.get_active() && self.cursor_visible && self.value.chars().count() < self.max_length && self.font.has_char(self.cursor) {
+            format!("{}{}{}", self.prefix, self.value, self.cursor)
+        } else {
+            format!("{}{}", self.prefix, self.value)
+        };
+        self.base.set_text(&text);
+    }
+    pub fn get_value(&self) -> &str {
+        &self.value
+    }
+    pub fn set_value(&mut self, value: &str) {
+        self.value = value.chars().take(self.max_length).collect();
+        self.update_text();
+    }
+    pub fn set_max_length(&mut self, max_length: usize) {
+        self.max_length = max_length;
+        self.value = self.value.chars().take(max_length).collect();
+        self.update_text();
+    }
+    pub fn set_cursor(&mut self, cursor: char) {
+        self.cursor = cursor;
+        self.update_text();
+    }
+}
+
+impl GMMenuItemT for GMMenuItemTextInput {
+    fn set_text(&mut self, text: &str) {
+        self.base.set_text(text);
+    }
+    fn draw(&self) {
+        self.base.draw();
+    }
+    fn update(&mut self) {
+        if self.cursor_timer.finished() {
+            self.cursor_visible = !self.cursor_visible;
+            self.update_text();
+        }
+        self.base.update();
+    }
+    fn set_active(&mut self, active: bool) {
+        self.base.set_active(active);
+        self.update_text();
+    }
+    fn get_active(&self) -> bool {
+        self.base.get_active()
+    }
+    fn event(&mut self, input: &GMInput) -> Option<GMMenuItemEvent> {
+        use GMMenuItemEvent::*;
+
+        let (mousex, mousey) = input.get_mouse_position();
+        let point_inside = self.base.point_inside(mousex, mousey);
+
+        if self.base.get_active() {
+            if input.is_pressed(MENU_UP) {
+                self.set_active(false);
+                Some(HighlightPrevItem)
+            } else if input.is_pressed(MENU_DOWN) {
+                self.set_active(false);
+                Some(HighlightNextItem)
+            } else if input.is_pressed(MENU_SELECT) || (input.is_pressed(MENU_CLICK) && point_inside) {
+                Some(SelectThisItem)
+            } else if input.is_pressed(MENU_BACKSPACE) {
+                self.value.pop()?;
+                self.update_text();
+                Some(NewValue(GMValue::String(self.value.clone())))
+            } else {
+                let mut changed = false;
+
+                for c in input.get_text().chars() {
+                    // Only characters that the font can draw
+                    if self.value.chars().count() < self.max_length && self.font.has_char(c) {
+                        self.value.push(c);
+                        changed = true;
+                    }
+                }
+
+                if changed {
+                    self.update_text();
+                    Some(NewValue(GMValue::String(self.value.clone())))
+                } else {
+                    None
+                }
+            }
+        } else {
+            if point_inside {
+                self.set_active(true);
+                Some(GMMenuItemEvent::HighlightThisItem)
+            } else {
+                None
+            }
+        }
+    }
+    fn set_font(&mut self, font: &GMFont) {
+        self.font = font.clone();
+        self.base.set_font(font);
+        self.update_text();
+    }
+    fn set_property(&mut self, data: &GMKeyValue) {
+        self.base.set_property(data);
+    }
+    fn set_x(&mut self, x: f32) {
+        self.base.set_x(x);
+    }
+    fn get_x(&self) -> f32 {
+        self.base.get_x()
+    }
+    fn set_y(&mut self, y: f32) {
+        self.base.set_y(y);
+    }
+    fn get_y(&self) -> f32 {
+        self.base.get_y()
+    }
+}
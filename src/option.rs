@@ -1,17 +1,83 @@
 
+use crate::input::{GMInputMapping, GMInputBinding};
+use crate::error::GMError;
+use crate::utils::GMValue;
 
+use macroquad::file::load_string;
+use nanoserde::{DeJson, SerJson};
 
-// TODO:
-// - keyboard / controller options
-// - load and save options
+use log::{info, error};
 
+use std::collections::HashMap;
+use std::fs;
 
+// Increase this when the file format changes and add a migration step in GMFormatOption::migrate()
+pub const GM_OPTION_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Default, DeJson, SerJson)]
+pub struct GMFormatInputAction {
+    action: String,
+    // See GMInputBinding::to_name()
+    bindings: Vec<String>,
+}
+
+#[derive(Clone, Debug, DeJson, SerJson)]
+pub struct GMFormatCustomValue {
+    key: String,
+    value: GMValue,
+}
+
+// Missing fields get the same default values as in GMOption::new()
+#[derive(Clone, Debug, DeJson, SerJson)]
+pub struct GMFormatOption {
+    // 0: file from before versioning was introduced
+    #[nserde(default)]
+    version: u32,
+    #[nserde(default = "1.0")]
+    sound_volume: f32,
+    #[nserde(default = "1.0")]
+    music_volume: f32,
+    #[nserde(default = "800.0")]
+    screen_width: f32,
+    #[nserde(default = "600.0")]
+    screen_height: f32,
+    #[nserde(default = "eng")]
+    language: String,
+    #[nserde(default)]
+    input_mapping: Vec<GMFormatInputAction>,
+    #[nserde(default)]
+    custom_values: Vec<GMFormatCustomValue>,
+}
+
+impl GMFormatOption {
+    // Brings an older file to the current version
+    fn migrate(&mut self) -> Result<(), GMError> {
+        // Files without version are from before versioning was introduced and are compatible with version 1
+        let version = self.version.max(1);
+
+        if version > GM_OPTION_VERSION {
+            return Err(GMError::VersionError(version))
+        }
+
+        // Migration steps go here, for ex.:
+        // if version < 2 { ... }
+
+        self.version = GM_OPTION_VERSION;
+        Ok(())
+    }
+}
+
+
+#[derive(Clone, Debug)]
 pub struct GMOption {
     sound_volume: f32,
     music_volume: f32,
     screen_width: f32,
     screen_height: f32,
     language: String,
+    input_mapping: GMInputMapping,
+    // Game specific settings
+    custom_values: HashMap<String, GMValue>,
 }
 
 impl GMOption {
@@ -22,8 +88,90 @@ impl GMOption {
             screen_width: 800.0,
             screen_height: 600.0,
             language: "eng".to_string(),
+            input_mapping: GMInputMapping::new_default(),
+            custom_values: HashMap::new(),
         }
     }
+    pub fn from_json(json: &str) -> Result<Self, GMError> {
+        let mut format: GMFormatOption = DeJson::deserialize_json(json)?;
+        format.migrate()?;
+
+        let mut option = Self::new();
+
+        option.sound_volume = format.sound_volume;
+        option.music_volume = format.music_volume;
+        option.screen_width = format.screen_width;
+        option.screen_height = format.screen_height;
+        option.language = format.language;
+
+        // Actions that are not in the file keep their default bindings
+        for item in format.input_mapping.iter() {
+            let mut bindings = Vec::new();
+
+            for name in item.bindings.iter() {
+                match GMInputBinding::from_name(name) {
+                    Some(binding) => bindings.push(binding),
+                    None => error!("GMOption::from_json(), unknown input binding: '{}'", name),
+                }
+            }
+
+            option.input_mapping.set_bindings(&item.action, &bindings);
+        }
+
+        for item in format.custom_values.into_iter() {
+            option.custom_values.insert(item.key, item.value);
+        }
+
+        Ok(option)
+    }
+    pub fn to_json(&self) -> String {
+        let mut actions = self.input_mapping.get_actions();
+        actions.sort();
+
+        let input_mapping = actions.iter().map(|action| GMFormatInputAction {
+            action: action.to_string(),
+            bindings: self.input_mapping.get_bindings(action).iter().map(|b| b.to_name()).collect(),
+        }).collect();
+
+        let mut custom_values: Vec<GMFormatCustomValue> = self.custom_values.iter().map(|(key, value)| GMFormatCustomValue {
+            key: key.clone(),
+            value: value.clone(),
+        }).collect();
+        custom_values.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let format = GMFormatOption {
+            version: GM_OPTION_VERSION,
+            sound_volume: self.sound_volume,
+            music_volume: self.music_volume,
+            screen_width: self.screen_width,
+            screen_height: self.screen_height,
+            language: self.language.clone(),
+            input_mapping,
+            custom_values,
+        };
+
+        SerJson::serialize_json(&format)
+    }
+    pub async fn load(file_name: &str) -> Result<Self, GMError> {
+        info!("Loading option file: '{}'", file_name);
+        let json = load_string(file_name).await?;
+        Self::from_json(&json)
+    }
+    // Use this on the first start of the game, when there is no option file yet
+    pub async fn load_or_default(file_name: &str) -> Self {
+        match Self::load(file_name).await {
+            Ok(option) => option,
+            Err(e) => {
+                error!("GMOption::load_or_default(), could not load option file '{}': {}", file_name, e);
+                Self::new()
+            }
+        }
+    }
+    pub fn save(&self, file_name: &str) -> Result<(), GMError> {
+        info!("Saving option file: '{}'", file_name);
+        fs::write(file_name, self.to_json())?;
+        Ok(())
+    }
     pub fn get_sound_volume(&self) -> f32 {
         self.sound_volume
     }
@@ -39,6 +187,12 @@ impl GMOption {
     pub fn get_language(&self) -> &str {
         &self.language
     }
+    pub fn get_input_mapping(&self) -> &GMInputMapping {
+        &self.input_mapping
+    }
+    pub fn get_input_mapping_mut(&mut self) -> &mut GMInputMapping {
+        &mut self.input_mapping
+    }
     pub fn set_sound_volume(&mut self, volume: f32) {
         self.sound_volume = volume;
     }
@@ -54,4 +208,16 @@ impl GMOption {
     pub fn set_language(&mut self, language: &str) {
         self.language = language.to_string();
     }
+    pub fn set_input_mapping(&mut self, input_mapping: GMInputMapping) {
+        self.input_mapping = input_mapping;
+    }
+    pub fn set_custom_value(&mut self, key: &str, value: GMValue) {
+        self.custom_values.insert(key.to_string(), value);
+    }
+    pub fn get_custom_value(&self, key: &str) -> Option<&GMValue> {
+        self.custom_values.get(key)
+    }
+    pub fn remove_custom_value(&mut self, key: &str) -> Option<GMValue> {
+        self.custom_values.remove(key)
+    }
 }
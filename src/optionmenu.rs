@@ -0,0 +1,309 @@
+use crate::font::GMFont;
+use crate::text::GMTextStatic;
+use crate::sprite::GMSpriteSimple;
+use crate::sound::GMSound;
+use crate::menu::GMMenu;
+use crate::menuitem::{GMMenuItem, GMMenuItemStatic, GMMenuItemNumeric, GMMenuItemEnum};
+use crate::option::GMOption;
+use crate::input::{GMInput, GMInputBinding, key_to_name};
+use crate::utils::GMValue;
+
+use macroquad::input::KeyCode;
+
+use std::rc::Rc;
+
+// TODO:
+// - change gamepad bindings
+
+#[derive(Clone, Debug, PartialEq)]
+enum GMOptionMenuEntry {
+    SoundVolume,
+    MusicVolume,
+    Resolution(Vec<(f32, f32)>),
+    // Names shown in the menu and language codes
+    Language(Vec<(String, String)>),
+    // Label and name of the action
+    KeyBinding(String, String),
+    Apply,
+    Cancel,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GMOptionMenuResult {
+    // Nothing has changed
+    None,
+    // A value has been written to the option
+    Changed,
+    // Apply has been selected, the option should be saved now
+    Applied,
+    // Cancel has been selected, the option has been restored
+    Cancelled,
+}
+
+#[derive(Clone)]
+pub struct GMOptionMenuBuilder {
+    x: f32,
+    y: f32,
+    title: String,
+    font: GMFont,
+    sprite: Option<GMSpriteSimple>,
+    change_sound: Rc<GMSound>,
+    enter_sound: Rc<GMSound>,
+    spacing: f32,
+    entries: Vec<(String, GMOptionMenuEntry)>,
+    apply_label: String,
+    cancel_label: String,
+    // Between the label and the key name of a key binding item
+    key_separator: String,
+}
+
+impl GMOptionMenuBuilder {
+    pub fn new(x: f32, y: f32, title: &str, font: &GMFont, change_sound: &Rc<GMSound>, enter_sound: &Rc<GMSound>) -> Self {
+        Self {
+            x,
+            y,
+            title: title.to_string(),
+            font: font.clone(),
+            sprite: None,
+            change_sound: change_sound.clone(),
+            enter_sound: enter_sound.clone(),
+            spacing: 40.0,
+            entries: Vec::new(),
+            apply_label: "APPLY".to_string(),
+            cancel_label: "CANCEL".to_string(),
+            key_separator: ": ".to_string(),
+        }
+    }
+    // Without a sprite an arrow is used for the highlighted item
+    pub fn sprite(mut self, sprite: &GMSpriteSimple) -> Self {
+        self.sprite = Some(sprite.clone());
+        self
+    }
+    pub fn spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    pub fn sound_volume(mut self, label: &str) -> Self {
+        self.entries.push((label.to_string(), GMOptionMenuEntry::SoundVolume));
+        self
+    }
+    pub fn music_volume(mut self, label: &str) -> Self {
+        self.entries.push((label.to_string(), GMOptionMenuEntry::MusicVolume));
+        self
+    }
+    pub fn resolution(mut self, label: &str, resolutions: &[(f32, f32)]) -> Self {
+        self.entries.push((label.to_string(), GMOptionMenuEntry::Resolution(resolutions.to_vec())));
+        self
+    }
+    // languages: name shown in the menu and language code
+    pub fn language(mut self, label: &str, languages: &[(&str, &str)]) -> Self {
+        let languages = languages.iter().map(|(name, code)| (name.to_string(), code.to_string())).collect();
+        self.entries.push((label.to_string(), GMOptionMenuEntry::Language(languages)));
+        self
+    }
+    pub fn key_binding(mut self, label: &str, action: &str) -> Self {
+        self.entries.push((label.to_string(), GMOptionMenuEntry::KeyBinding(label.to_string(), action.to_string())));
+        self
+    }
+    pub fn key_separator(mut self, key_separator: &str) -> Self {
+        self.key_separator = key_separator.to_string();
+        self
+    }
+    pub fn apply_cancel(mut self, apply_label: &str, cancel_label: &str) -> Self {
+        self.apply_label = apply_label.to_string();
+        self.cancel_label = cancel_label.to_string();
+        self
+    }
+    fn static_item(&self, text: &str, x: f32, y: f32) -> GMMenuItem {
+        match &self.sprite {
+            Some(sprite) => GMMenuItemStatic::new_static_sprite(text, x, y, &self.font, sprite),
+            None => GMMenuItemStatic::new_static_arrow(text, x, y, &self.font),
+        }
+    }
+    fn numeric_item(&self, text: &str, x: f32, y: f32, current_val: f32) -> GMMenuItem {
+        match &self.sprite {
+            Some(sprite) => GMMenuItemNumeric::new_static_sprite(text, x, y, &self.font, sprite, 0.0, 1.0, current_val, 0.1),
+            None => GMMenuItemNumeric::new_static_arrow(text, x, y, &self.font, 0.0, 1.0, current_val, 0.1),
+        }
+    }
+    fn enum_item(&self, text: &str, x: f32, y: f32, items: &[&str], current_item: usize) -> GMMenuItem {
+        match &self.sprite {
+            Some(sprite) => GMMenuItemEnum::new_static_sprite(text, x, y, &self.font, sprite, items, current_item),
+            None => GMMenuItemEnum::new_static_arrow(text, x, y, &self.font, items, current_item),
+        }
+    }
+    fn build_menu(&self, option: &GMOption) -> (GMMenu, Vec<GMOptionMenuEntry>) {
+        let title = GMTextStatic::new_box(&self.title, self.x, self.y, &self.font);
+        let mut menu = GMMenu::new_empty(title, &self.change_sound, &self.enter_sound);
+        let mut entries = Vec::new();
+        let item_y = self.y + self.spacing * 1.5;
+
+        for (label, entry) in self.entries.iter() {
+            use GMOptionMenuEntry::*;
+
+            let item = match entry {
+                SoundVolume => self.numeric_item(label, self.x, item_y, option.get_sound_volume()),
+                MusicVolume => self.numeric_item(label, self.x, item_y, option.get_music_volume()),
+                Resolution(resolutions) => {
+                    let names: Vec<String> = resolutions.iter().map(|(w, h)| format!("{}X{}", w, h)).collect();
+                    let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+                    let current = resolutions.iter()
+                        .position(|(w, h)| *w == option.get_screen_width() && *h == option.get_screen_height())
+                        .unwrap_or(0);
+                    self.enum_item(label, self.x, item_y, &names, current)
+                }
+                Language(languages) => {
+                    let names: Vec<&str> = languages.iter().map(|(name, _)| name.as_str()).collect();
+                    let current = languages.iter().position(|(_, code)| code == option.get_language()).unwrap_or(0);
+                    self.enum_item(label, self.x, item_y, &names, current)
+                }
+                KeyBinding(label, action) => {
+                    self.static_item(&key_binding_text(label, &self.key_separator, option, action), self.x, item_y)
+                }
+                Apply | Cancel => unreachable!(),
+            };
+
+            menu.add_item(item, 0.0, self.spacing);
+            entries.push(entry.clone());
+        }
+
+        menu.add_item(self.static_item(&self.apply_label, self.x, item_y), 0.0, self.spacing);
+        entries.push(GMOptionMenuEntry::Apply);
+        menu.add_item(self.static_item(&self.cancel_label, self.x, item_y), 0.0, self.spacing);
+        entries.push(GMOptionMenuEntry::Cancel);
+
+        (menu, entries)
+    }
+    pub fn build(self, option: &GMOption) -> GMOptionMenu {
+        let (menu, entries) = self.build_menu(option);
+
+        GMOptionMenu {
+            builder: self,
+            menu,
+            entries,
+            original: option.clone(),
+            waiting_for_key: None,
+        }
+    }
+}
+
+// Shows the label and the first key bound to the action
+fn key_binding_text(label: &str, separator: &str, option: &GMOption, action: &str) -> String {
+    let key = option.get_input_mapping().get_bindings(action).iter().find_map(|binding| {
+        match binding {
+            GMInputBinding::Key(key) => Some(key_to_name(*key).to_uppercase()),
+            _ => None,
+        }
+    });
+
+    match key {
+        Some(key) => format!("{}{}{}", label, separator, key),
+        None => label.to_string(),
+    }
+}
+
+// All changes are written into the option immediately, so they can be seen (or heard) right away.
+// Cancel restores the option to the state it had when the menu was built or when apply was selected.
+pub struct GMOptionMenu {
+    // Used to rebuild the menu items after cancel
+    builder: GMOptionMenuBuilder,
+    menu: GMMenu,
+    entries: Vec<GMOptionMenuEntry>,
+    original: GMOption,
+    // Index of the key binding item that waits for a key press
+    waiting_for_key: Option<usize>,
+}
+
+impl GMOptionMenu {
+    pub fn draw(&self) {
+        self.menu.draw();
+    }
+    pub fn update(&mut self) {
+        self.menu.update();
+    }
+    pub fn get_menu(&mut self) -> &mut GMMenu {
+        &mut self.menu
+    }
+    pub fn is_waiting_for_key(&self) -> bool {
+        self.waiting_for_key.is_some()
+    }
+    fn key_event(&mut self, i: usize, input: &GMInput, option: &mut GMOption) -> GMOptionMenuResult {
+        let key = match input.get_last_key() {
+            Some(key) => key,
+            None => return GMOptionMenuResult::None,
+        };
+
+        self.waiting_for_key = None;
+
+        if let GMOptionMenuEntry::KeyBinding(label, action) = &self.entries[i] {
+            // Escape keeps the old binding
+            if key != KeyCode::Escape {
+                // Only the keyboard bindings are replaced
+                let mapping = option.get_input_mapping_mut();
+                let mut bindings: Vec<GMInputBinding> = mapping.get_bindings(action).iter()
+                    .filter(|binding| !matches!(binding, GMInputBinding::Key(_)))
+                    .copied()
+                    .collect();
+                bindings.insert(0, GMInputBinding::Key(key));
+                mapping.set_bindings(action, &bindings);
+            }
+
+            let text = key_binding_text(label, &self.builder.key_separator, option, action);
+            self.menu.set_item_text(i, &text);
+        }
+
+        GMOptionMenuResult::Changed
+    }
+    pub fn event(&mut self, input: &GMInput, option: &mut GMOption) -> GMOptionMenuResult {
+        use GMOptionMenuEntry::*;
+
+        if let Some(i) = self.waiting_for_key {
+            return self.key_event(i, input, option)
+        }
+
+        let (i, value) = match self.menu.event(input) {
+            Some(result) => result,
+            None => return GMOptionMenuResult::None,
+        };
+
+        match (&self.entries[i], value) {
+            (SoundVolume, GMValue::F32(volume)) => {
+                option.set_sound_volume(volume);
+                GMOptionMenuResult::Changed
+            }
+            (MusicVolume, GMValue::F32(volume)) => {
+                option.set_music_volume(volume);
+                GMOptionMenuResult::Changed
+            }
+            (Resolution(resolutions), GMValue::USize(j)) => {
+                let (width, height) = resolutions[j];
+                option.set_screen_width(width);
+                option.set_screen_height(height);
+                GMOptionMenuResult::Changed
+            }
+            (Language(languages), GMValue::USize(j)) => {
+                option.set_language(&languages[j].1);
+                GMOptionMenuResult::Changed
+            }
+            (KeyBinding(label, _), GMValue::None) => {
+                // Only the label is shown until a key is pressed
+                let label = label.clone();
+                self.menu.set_item_text(i, &label);
+                self.waiting_for_key = Some(i);
+                GMOptionMenuResult::None
+            }
+            (Apply, GMValue::None) => {
+                self.original = option.clone();
+                GMOptionMenuResult::Applied
+            }
+            (Cancel, GMValue::None) => {
+                *option = self.original.clone();
+                // Show the restored values
+                let (menu, _) = self.builder.build_menu(option);
+                self.menu = menu;
+                GMOptionMenuResult::Cancelled
+            }
+            _ => GMOptionMenuResult::None,
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::resources::GMResourceManager;
 use crate::sprite::GMSprite;
+use crate::clock::get_time;
 
-use macroquad::time::get_time;
 use macroquad::rand::gen_range;
 
 use std::f32::consts;
@@ -28,8 +28,8 @@ impl GMParticle {
     pub fn draw(&self) {
         self.sprite.draw();
     }
-    pub fn update(&mut self) {
-        self.sprite.update();
+    pub fn update(&mut self, dt: f32) {
+        self.sprite.update(dt);
     }
 }
 
@@ -55,8 +55,8 @@ impl GMParticleManager {
             x,
             y,
             active: false,
-            speed_min: 1.0,
-            speed_max: 5.0,
+            speed_min: 60.0,
+            speed_max: 300.0,
             rotation_speed_min: 0.0,
             rotation_speed_max: 0.0,
             duration: 5.0,
@@ -103,10 +103,10 @@ impl GMParticleManager {
             }
         }
     }
-    pub fn update(&mut self) {
+    pub fn update(&mut self, dt: f32) {
         if self.active {
             for p in self.particles.iter_mut() {
-                p.update();
+                p.update(dt);
             }
 
             let time = get_time();
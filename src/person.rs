@@ -34,8 +34,8 @@ impl GMPerson {
         self.sprite.draw();
         self.health_bar.draw();
     }
-    pub fn update(&mut self) {
-        self.sprite.update();
+    pub fn update(&mut self, dt: f32) {
+        self.sprite.update(dt);
         self.health_bar.update();
     }
 }
@@ -0,0 +1,89 @@
+use crate::error::GMError;
+use crate::input::GMInputFrame;
+
+use macroquad::file::load_string;
+use nanoserde::{DeJson, SerJson};
+
+use log::info;
+
+use std::fs;
+
+// Increase this when the file format changes
+pub const GM_REPLAY_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Default, PartialEq, DeJson, SerJson)]
+pub struct GMReplayFrame {
+    // Frame time in seconds
+    pub dt: f32,
+    pub input: GMInputFrame,
+}
+
+// Only the version, read first so that replays with another format give a VersionError
+#[derive(DeJson)]
+struct GMReplayHeader {
+    version: u32,
+}
+
+// Everything that is needed to run the game again in exactly the same way:
+// the seed for the random number generator and the frame time and input state of every frame.
+#[derive(Clone, Debug, PartialEq, DeJson, SerJson)]
+pub struct GMReplay {
+    version: u32,
+    seed: u64,
+    frames: Vec<GMReplayFrame>,
+}
+
+impl GMReplay {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            version: GM_REPLAY_VERSION,
+            seed,
+            frames: Vec::new(),
+        }
+    }
+    pub fn from_json(json: &str) -> Result<Self, GMError> {
+        let header: GMReplayHeader = DeJson::deserialize_json(json)?;
+
+        if header.version != GM_REPLAY_VERSION {
+            return Err(GMError::VersionError(header.version))
+        }
+
+        let replay: GMReplay = DeJson::deserialize_json(json)?;
+        Ok(replay)
+    }
+    pub fn to_json(&self) -> String {
+        SerJson::serialize_json(self)
+    }
+    pub async fn load_from_file(file_name: &str) -> Result<Self, GMError> {
+        info!("Loading replay file: '{}'", file_name);
+        let json = load_string(file_name).await?;
+        Self::from_json(&json)
+    }
+    pub fn save_to_file(&self, file_name: &str) -> Result<(), GMError> {
+        info!("Saving replay file: '{}'", file_name);
+        fs::write(file_name, self.to_json())?;
+        Ok(())
+    }
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+    pub fn add_frame(&mut self, frame: GMReplayFrame) {
+        self.frames.push(frame);
+    }
+    pub fn get_frame(&self, index: usize) -> Option<&GMReplayFrame> {
+        self.frames.get(index)
+    }
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+pub(crate) enum GMReplayState {
+    Off,
+    Recording(GMReplay),
+    // Replay and index of the next frame
+    Playing(GMReplay, usize),
+}
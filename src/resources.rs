@@ -3,18 +3,20 @@
 use crate::error::GMError;
 use crate::font::{GMBitmapFont, GMFont};
 use crate::spritesheet::GMSpriteSheet;
-use crate::sprite::{GMSprite, GMSpriteSingle, GMSpriteSimple};
+use crate::sprite::{GMSprite, GMSpriteSingle, GMSpriteSimple, GMCollisionShape};
 use crate::sound::GMSound;
 use crate::animation::{GMAnimationBackwardLoop, GMAnimationBackwardOnce, GMAnimationForwardLoop, GMAnimationForwardOnce, GMAnimationPingPong, GMAnimation};
 use crate::tilemap::GMTileMap;
 use crate::tileset::GMTileSet;
 use crate::tilewindow::GMTileWindow;
+use crate::localization::GMLocalization;
+use crate::spritestate::{GMSpriteStateTable, GMSpriteState, GMSpriteStateMachine, GMStateCondition};
 
 use macroquad::file::load_string;
 use macroquad::math::Rect;
 use nanoserde::DeJson;
 
-use log::{debug, info};
+use log::{debug, info, error};
 
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -34,10 +36,12 @@ pub struct GMFormatResource {
     sprites: Option<Vec<GMFormatSprite>>,
     sounds: Option<Vec<GMFormatSound>>,
     animation_files: Option<Vec<String>>,
+    sprite_states: Option<Vec<GMFormatStateTable>>,
     tileset_files: Option<Vec<String>>,
     tilemap_files: Option<Vec<String>>,
     tile_windows: Option<Vec<GMFormatTileWindow>>,
     borders: Option<Vec<GMFormatBorder>>,
+    string_table_files: Option<Vec<String>>,
 }
 
 #[derive(Clone, Debug, Default, DeJson)]
@@ -60,6 +64,60 @@ pub struct GMFormatSprite {
     name: String,
     sprite_sheet: String,
     animation: String,
+    // Pixel perfect collision, the masks are built while loading
+    #[nserde(default)]
+    collision_mask: bool,
+    // Name of an entry in sprite_states
+    #[nserde(default)]
+    state_table: String,
+}
+
+#[derive(Clone, Debug, Default, DeJson)]
+pub struct GMFormatSpriteState {
+    name: String,
+    animation: String,
+    #[nserde(default)]
+    flip_x: bool,
+    #[nserde(default)]
+    flip_y: bool,
+    // "rectangle", "circle", "rotated_rectangle", "polygon" or "mask", empty keeps the current shape
+    #[nserde(default)]
+    collision_shape: String,
+    #[nserde(default)]
+    polygon: Vec<(f32, f32)>,
+}
+
+#[derive(Clone, Debug, Default, DeJson)]
+pub struct GMFormatStateTransition {
+    // "*" for any state
+    from: String,
+    to: String,
+    // "animation_finished", "flag_name" or "!flag_name"
+    condition: String,
+}
+
+#[derive(Clone, Debug, Default, DeJson)]
+pub struct GMFormatStateTable {
+    name: String,
+    // The first state is the start state
+    states: Vec<GMFormatSpriteState>,
+    #[nserde(default)]
+    transitions: Vec<GMFormatStateTransition>,
+}
+
+fn collision_shape_from_format(state: &GMFormatSpriteState) -> Option<GMCollisionShape> {
+    match state.collision_shape.as_str() {
+        "" => None,
+        "rectangle" => Some(GMCollisionShape::Rectangle),
+        "circle" => Some(GMCollisionShape::Circle),
+        "rotated_rectangle" => Some(GMCollisionShape::RotatedRectangle),
+        "polygon" => Some(GMCollisionShape::Polygon(state.polygon.clone())),
+        "mask" => Some(GMCollisionShape::Mask),
+        name => {
+            error!("Unknown collision shape: '{}' in state '{}'", name, state.name);
+            None
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, DeJson)]
@@ -138,9 +196,11 @@ pub struct GMResourceManager {
     sprites: HashMap<String, GMSprite>,
     sounds: HashMap<String, Rc<GMSound>>,
     animations: HashMap<String, GMAnimation>,
+    state_tables: HashMap<String, Rc<GMSpriteStateTable>>,
     tileset: HashMap<String, Rc<GMTileSet>>,
     tilemap: HashMap<String, GMTileMap>,
     tile_window: HashMap<String, GMTileWindow>,
+    localization: Rc<GMLocalization>,
 }
 
 impl GMResourceManager {
@@ -151,9 +211,11 @@ impl GMResourceManager {
             sprites: HashMap::new(),
             sounds: HashMap::new(),
             animations: HashMap::new(),
+            state_tables: HashMap::new(),
             tileset: HashMap::new(),
             tilemap: HashMap::new(),
             tile_window: HashMap::new(),
+            localization: Rc::new(GMLocalization::default()),
         }
     }
     pub async fn new_from_file(file_name: &str) -> Result<Self, GMError> {
@@ -188,14 +250,40 @@ impl GMResourceManager {
                 resource.animations_from_file(file_name).await?;
             }
         }
+        if let Some(sprite_states) = result.sprite_states {
+            for item in sprite_states.iter() {
+                resource.state_table_from_format(item);
+            }
+        }
         if let Some(sprites) = result.sprites {
             for item in sprites.into_iter() {
                 debug!("Sprite name: '{}', sprite sheet: '{}', animation: '{}'", item.name,
                     item.sprite_sheet, item.animation);
 
-                let sprite = GMSpriteSingle::new_wrapped(
-                    &resource.get_sprite_sheet(&item.sprite_sheet).unwrap(),
-                    &resource.get_animation(&item.animation).unwrap(), 0.0, 0.0);
+                let sprite_sheet = resource.get_sprite_sheet(&item.sprite_sheet).unwrap();
+                let animation = resource.get_animation(&item.animation).unwrap();
+                let mut sprite = GMSpriteSingle::new_wrapped(&sprite_sheet, &animation, 0.0, 0.0);
+
+                if item.collision_mask {
+                    sprite_sheet.build_masks(&animation);
+                    sprite.set_collision_shape(GMCollisionShape::Mask);
+                }
+
+                if !item.state_table.is_empty() {
+                    let table = resource.get_state_table(&item.state_table).unwrap();
+
+                    // Masks of all states are built now as well
+                    for id in 0..table.get_num_of_states() {
+                        let state = table.get_state(id as u32).unwrap();
+
+                        if state.get_collision_shape() == Some(&GMCollisionShape::Mask) {
+                            sprite_sheet.build_masks(state.get_animation());
+                        }
+                    }
+
+                    sprite.set_state_machine(GMSpriteStateMachine::new(&table));
+                }
+
                 resource.sprites.insert(item.name, sprite);
             }
         }
@@ -219,6 +307,11 @@ impl GMResourceManager {
                 resource.tile_window.insert(item.name, tile_window);
             }            
         }
+        if let Some(string_table_files) = result.string_table_files {
+            for file_name in string_table_files.iter() {
+                resource.string_table_from_file(file_name).await?;
+            }
+        }
 
         Ok(resource)
     }
@@ -277,11 +370,7 @@ impl GMResourceManager {
         self.sprites.get(name)
     }
     pub fn get_sprite_simple(&self, name: &str) -> Option<GMSpriteSimple> {
-        if let Some(sprite) = self.sprites.get(name) {
-            Some(sprite.to_simple())
-        } else {
-            None
-        }
+        self.sprites.get(name).and_then(|sprite| sprite.to_simple())
     }
     pub fn remove_sprite(&mut self, name: &str) -> Option<GMSprite> {
         self.sprites.remove(name)
@@ -337,6 +426,40 @@ impl GMResourceManager {
     pub fn clear_animations(&mut self) {
         self.animations.clear();
     }
+    fn state_table_from_format(&mut self, item: &GMFormatStateTable) {
+        debug!("State table name: '{}'", item.name);
+
+        let mut table = GMSpriteStateTable::new();
+
+        for format_state in item.states.iter() {
+            let animation = self.get_animation(&format_state.animation).unwrap();
+            let mut state = GMSpriteState::new(&format_state.name, &animation, format_state.flip_x, format_state.flip_y);
+
+            if let Some(collision_shape) = collision_shape_from_format(format_state) {
+                state.set_collision_shape(collision_shape);
+            }
+
+            table.add_state(state);
+        }
+
+        for transition in item.transitions.iter() {
+            table.add_transition(&transition.from, &transition.to, GMStateCondition::from_name(&transition.condition));
+        }
+
+        self.state_tables.insert(item.name.clone(), Rc::new(table));
+    }
+    pub fn add_state_table(&mut self, name: &str, table: GMSpriteStateTable) {
+        self.state_tables.insert(name.to_string(), Rc::new(table));
+    }
+    pub fn get_state_table(&self, name: &str) -> Option<Rc<GMSpriteStateTable>> {
+        self.state_tables.get(name).cloned()
+    }
+    pub fn remove_state_table(&mut self, name: &str) -> Option<Rc<GMSpriteStateTable>> {
+        self.state_tables.remove(name)
+    }
+    pub fn clear_state_tables(&mut self) {
+        self.state_tables.clear();
+    }
     pub fn add_sound(&mut self, name: &str, sound: GMSound) {
         self.sounds.insert(name.to_string(), Rc::new(sound));
     }
@@ -402,11 +525,19 @@ impl GMResourceManager {
     pub fn clear_tile_window(&mut self) {
         self.tile_window.clear();
     }
+    pub async fn string_table_from_file(&mut self, file_name: &str) -> Result<(), GMError> {
+        self.localization.table_from_file(file_name).await
+    }
+    // All localized texts share this, see GMTextLocalized
+    pub fn get_localization(&self) -> Rc<GMLocalization> {
+        self.localization.clone()
+    }
     pub fn clear_all(&mut self) {
         self.clear_fonts();
         self.clear_sprite_sheets();
         self.clear_sprites();
         self.clear_animations();
+        self.clear_state_tables();
         self.clear_sounds();
     }
 }
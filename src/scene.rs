@@ -1,57 +1,331 @@
+use crate::context::GMContext;
+use crate::clock::{GMClock, set_active_clock};
+use crate::transition::{GMTransition, GMTransitionState};
+use crate::replay::{GMReplay, GMReplayFrame, GMReplayState};
 
 use macroquad::window::next_frame;
+use macroquad::time::get_frame_time;
+#[cfg(not(target_arch = "wasm32"))]
+use macroquad::time::get_time;
+use macroquad::rand::srand;
+
+use std::rc::Rc;
+
+use log::{error, info};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+// Upper limit for the frame time, avoids a huge number of fixed updates after a hiccup
+const MAX_FRAME_TIME: f32 = 0.25;
+// Upper limit for the fixed updates per frame, if the game can not keep up the rest of the time is dropped
+const MAX_FIXED_STEPS: u32 = 10;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GMLoopMode {
+    // update() is called once per frame with the measured frame time
+    Variable,
+    // update() is called with a fixed time step, given as updates per second (Hz)
+    Fixed(f32),
+}
 
 pub enum GMSceneResult {
     KeepScene,
     ChangeScene(String),
+    ChangeSceneWith(String, GMTransition),
+    PushScene(String),
+    PopScene,
     Exit,
 }
 
-pub trait GMSceneT {
-    fn init(&mut self);
-    fn draw(&self);
-    fn update(&mut self);
-    fn event(&mut self) -> GMSceneResult;
+pub trait GMSceneT<T = ()> {
+    fn draw(&self, context: &GMContext<T>);
+    // dt is the time step in seconds
+    fn update(&mut self, context: &mut GMContext<T>, dt: f32);
+    fn event(&mut self, context: &mut GMContext<T>) -> GMSceneResult;
+    // Should the scenes below this one (on the scene stack) still be drawn ?
+    fn draw_below(&self) -> bool {
+        false
+    }
+    // Should the scenes below this one (on the scene stack) still be updated ?
+    fn update_below(&self) -> bool {
+        false
+    }
+    // The scene becomes the current scene, from is the name of the previous current scene
+    fn on_enter(&mut self, _context: &mut GMContext<T>, _from: Option<&str>) {}
+    // The scene leaves the scene stack, to is the name of the next current scene
+    fn on_exit(&mut self, _context: &mut GMContext<T>, _to: Option<&str>) {}
+    // Another scene has been pushed on top of this one.
+    // Pause the scene clock here if time should stand still while the scene is not updated.
+    fn on_suspend(&mut self, _context: &mut GMContext<T>) {}
+    // The scene on top of this one has been popped, this is the current scene again
+    fn on_resume(&mut self, _context: &mut GMContext<T>) {}
+    // The scene is removed from the scene manager and will be dropped
+    fn on_remove(&mut self, _context: &mut GMContext<T>) {}
 }
 
-pub struct GMSceneManager {
-    scenes: Vec<(String, Box<dyn GMSceneT>)>,
-    current_scene: usize,
+// Name, scene and the scene clock, which is a child of the game clock
+type GMSceneEntry<T> = (String, Box<dyn GMSceneT<T>>, Rc<GMClock>);
+
+pub struct GMSceneManager<T = ()> {
+    scenes: Vec<GMSceneEntry<T>>,
+    // Indices into scenes, the last one is the current (top) scene
+    scene_stack: Vec<usize>,
+    // Running transition and the scene stack that is faded out
+    transition: Option<(GMTransitionState, Vec<usize>)>,
+    context: GMContext<T>,
+    clock: Rc<GMClock>,
+    loop_mode: GMLoopMode,
+    max_fps: Option<f32>,
+    accumulator: f32,
+    replay: GMReplayState,
 }
 
-impl GMSceneManager {
-    pub fn new() -> Self {
+impl<T> GMSceneManager<T> {
+    pub fn new(context: GMContext<T>) -> Self {
+        // Advanced with the frame time in start_loop(), so that replays run with the recorded times
+        let clock = Rc::new(GMClock::new_manual());
+        // Animations and timers created before the loop runs use the game clock, too
+        set_active_clock(&clock);
+
         Self {
             scenes: Vec::new(),
-            current_scene: 0,
+            scene_stack: Vec::new(),
+            transition: None,
+            context,
+            clock,
+            loop_mode: GMLoopMode::Variable,
+            max_fps: None,
+            accumulator: 0.0,
+            replay: GMReplayState::Off,
         }
     }
 
-    pub fn add_scene(&mut self, name: &str, scene: Box<dyn GMSceneT>) {
-        self.scenes.push((name.to_string(), scene));
+    pub fn add_scene(&mut self, name: &str, scene: Box<dyn GMSceneT<T>>) {
+        let clock = Rc::new(GMClock::new_child(&self.clock));
+        self.scenes.push((name.to_string(), scene, clock));
+
+        let i = self.scenes.len() - 1;
+        self.activate(i);
 
-        if self.scenes.len() == 1 {
-            self.scenes[0].1.init();
+        if self.scene_stack.is_empty() {
+            self.scene_stack.push(i);
+            self.scenes[i].1.on_enter(&mut self.context, None);
         }
     }
 
     pub fn remove_scene(&mut self, name: &str) {
-        let result = self.scenes.iter().position(|x| x.0 == name);
-        if let Some(i) = result {
-            self.scenes.remove(i);
+        let i = match self.scene_index(name) {
+            Some(i) => i,
+            None => {
+                error!("GMSceneManager::remove_scene(), unknown scene '{}'", name);
+                return
+            }
+        };
+
+        if let Some(pos) = self.scene_stack.iter().position(|s| *s == i) {
+            let was_current = pos == self.scene_stack.len() - 1;
+            self.scene_stack.remove(pos);
+
+            let next_name = self.current_name();
+            self.activate(i);
+            self.scenes[i].1.on_exit(&mut self.context, next_name.as_deref());
+
+            if was_current {
+                if let Some(top) = self.scene_stack.last().copied() {
+                    self.activate(top);
+                    self.scenes[top].1.on_resume(&mut self.context);
+                }
+            }
+        }
+
+        self.activate(i);
+        self.scenes[i].1.on_remove(&mut self.context);
+        self.scenes.remove(i);
+
+        // All indices above the removed scene are shifted down by one
+        let fix_stack = |stack: &mut Vec<usize>| {
+            stack.retain(|s| *s != i);
+
+            for s in stack.iter_mut() {
+                if *s > i {
+                    *s -= 1;
+                }
+            }
+        };
+
+        fix_stack(&mut self.scene_stack);
+
+        if let Some((_, old_stack)) = &mut self.transition {
+            fix_stack(old_stack);
+        }
+    }
+
+    // The game clock, all scene clocks depend on it
+    pub fn get_clock(&self) -> &Rc<GMClock> {
+        &self.clock
+    }
+
+    pub fn get_scene_clock(&self, name: &str) -> Option<Rc<GMClock>> {
+        self.scene_index(name).map(|i| self.scenes[i].2.clone())
+    }
+
+    // Set the clock of the given scene as the active clock for all time dependent code
+    fn activate(&self, i: usize) {
+        set_active_clock(&self.scenes[i].2);
+    }
+
+    pub fn get_context(&self) -> &GMContext<T> {
+        &self.context
+    }
+
+    pub fn get_context_mut(&mut self) -> &mut GMContext<T> {
+        &mut self.context
+    }
+
+    pub fn set_loop_mode(&mut self, loop_mode: GMLoopMode) {
+        if let GMLoopMode::Fixed(hz) = loop_mode {
+            if !(hz.is_finite() && hz > 0.0) {
+                error!("GMSceneManager::set_loop_mode(), invalid update rate: {}", hz);
+                return
+            }
+        }
+
+        self.loop_mode = loop_mode;
+        self.accumulator = 0.0;
+    }
+
+    pub fn get_loop_mode(&self) -> GMLoopMode {
+        self.loop_mode
+    }
+
+    // Has no effect on wasm, the browser limits the frame rate there
+    pub fn set_max_fps(&mut self, max_fps: Option<f32>) {
+        if let Some(fps) = max_fps {
+            if !(fps.is_finite() && fps > 0.0) {
+                error!("GMSceneManager::set_max_fps(), invalid frame rate: {}", fps);
+                return
+            }
+        }
+
+        self.max_fps = max_fps;
+    }
+
+    pub fn get_current_scene(&self) -> Option<&str> {
+        self.scene_stack.last().map(|i| self.scenes[*i].0.as_str())
+    }
+
+    pub fn get_stack_depth(&self) -> usize {
+        self.scene_stack.len()
+    }
+
+    fn scene_index(&self, name: &str) -> Option<usize> {
+        self.scenes.iter().position(|x| x.0 == name)
+    }
+
+    fn current_name(&self) -> Option<String> {
+        self.get_current_scene().map(|name| name.to_string())
+    }
+
+    pub fn in_transition(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    fn draw_stack(&self, stack: &[usize]) {
+        if stack.is_empty() {
+            return
+        }
+
+        // Find the lowest scene in the stack that is still visible
+        let mut lowest = stack.len() - 1;
+
+        while lowest > 0 && self.scenes[stack[lowest]].1.draw_below() {
+            lowest -= 1;
+        }
+
+        for i in stack[lowest..].iter() {
+            self.activate(*i);
+            self.scenes[*i].1.draw(&self.context);
         }
     }
 
     pub fn draw(&self)  {
-        self.scenes[self.current_scene].1.draw();
+        match &self.transition {
+            Some((transition, old_stack)) => {
+                transition.begin_old();
+                self.draw_stack(old_stack);
+                transition.begin_new();
+                self.draw_stack(&self.scene_stack);
+                set_active_clock(&self.clock);
+                transition.draw();
+            }
+            None => {
+                self.draw_stack(&self.scene_stack);
+            }
+        }
     }
 
-    pub fn update(&mut self) {
-        self.scenes[self.current_scene].1.update();
+    pub fn update(&mut self, dt: f32) {
+        set_active_clock(&self.clock);
+
+        if let Some((transition, _)) = &self.transition {
+            if transition.finished() {
+                self.transition = None;
+            }
+        }
+
+        for i in (0..self.scene_stack.len()).rev() {
+            let index = self.scene_stack[i];
+            self.activate(index);
+
+            let (_, scene, clock) = &mut self.scenes[index];
+            // Paused or slowed down scenes get a smaller time step
+            scene.update(&mut self.context, dt * (clock.get_local_scale() as f32));
+
+            if !scene.update_below() {
+                break;
+            }
+        }
+    }
+
+    fn change_scene(&mut self, new_scene: &str) -> bool {
+        match self.scene_index(new_scene) {
+            Some(i) => {
+                let old_name = self.current_name();
+
+                // Changing the scene throws away all the scenes on the stack
+                while let Some(old) = self.scene_stack.pop() {
+                    self.activate(old);
+                    self.scenes[old].1.on_exit(&mut self.context, Some(new_scene));
+                }
+
+                self.scene_stack.push(i);
+                self.activate(i);
+                self.scenes[i].1.on_enter(&mut self.context, old_name.as_deref());
+                true
+            }
+            None => {
+                error!("GMSceneManager::event(), ChangeScene: unknown scene '{}'", new_scene);
+                false
+            }
+        }
     }
 
     pub fn event(&mut self) -> bool {
-        let result = self.scenes[self.current_scene].1.event();
+        if self.transition.is_some() {
+            // No user input while the transition is running
+            return false
+        }
+
+        let current_scene = match self.scene_stack.last() {
+            Some(i) => *i,
+            None => return true,
+        };
+
+        self.activate(current_scene);
+        let result = self.scenes[current_scene].1.event(&mut self.context);
 
         use GMSceneResult::*;
 
@@ -60,18 +334,50 @@ impl GMSceneManager {
                 false
             }
             ChangeScene(new_scene) => {
-                let mut change_scene = false;
+                self.change_scene(&new_scene);
+                false
+            }
+            ChangeSceneWith(new_scene, transition) => {
+                let old_stack = self.scene_stack.clone();
 
-                for i in 0..self.scenes.len() {
-                    if self.scenes[i].0 == new_scene {
-                        self.current_scene = i;
-                        change_scene = true;
-                        break;
+                if self.change_scene(&new_scene) {
+                    self.transition = Some((GMTransitionState::new(transition, &self.clock), old_stack));
+                }
+
+                false
+            }
+            PushScene(new_scene) => {
+                match self.scene_index(&new_scene) {
+                    Some(i) if self.scene_stack.contains(&i) => {
+                        error!("GMSceneManager::event(), PushScene: scene '{}' is already on the stack", new_scene);
+                    }
+                    Some(i) => {
+                        // The scenes below keep their state, they are just suspended
+                        let old_name = self.current_name();
+                        self.scenes[current_scene].1.on_suspend(&mut self.context);
+                        self.scene_stack.push(i);
+                        self.activate(i);
+                        self.scenes[i].1.on_enter(&mut self.context, old_name.as_deref());
+                    }
+                    None => {
+                        error!("GMSceneManager::event(), PushScene: unknown scene '{}'", new_scene);
                     }
                 }
 
-                if change_scene {
-                    self.scenes[self.current_scene].1.init();
+                false
+            }
+            PopScene => {
+                if self.scene_stack.len() > 1 {
+                    self.scene_stack.pop();
+                    let next_name = self.current_name();
+                    self.scenes[current_scene].1.on_exit(&mut self.context, next_name.as_deref());
+
+                    if let Some(top) = self.scene_stack.last().copied() {
+                        self.activate(top);
+                        self.scenes[top].1.on_resume(&mut self.context);
+                    }
+                } else {
+                    error!("GMSceneManager::event(), PopScene: can not pop the last scene");
                 }
 
                 false
@@ -82,15 +388,120 @@ impl GMSceneManager {
         }
     }
 
+    fn update_frame(&mut self, frame_time: f32) {
+        match self.loop_mode {
+            GMLoopMode::Variable => {
+                self.update(frame_time * (self.clock.get_effective_scale() as f32));
+                self.context.set_alpha(1.0);
+            }
+            GMLoopMode::Fixed(hz) => {
+                let step = 1.0 / hz;
+                self.accumulator += frame_time.min(MAX_FRAME_TIME) * (self.clock.get_effective_scale() as f32);
+
+                let mut steps = 0;
+
+                while self.accumulator >= step {
+                    if steps == MAX_FIXED_STEPS {
+                        self.accumulator %= step;
+                        break;
+                    }
+
+                    self.update(step);
+                    self.accumulator -= step;
+                    steps += 1;
+                }
+
+                self.context.set_alpha(self.accumulator / step);
+            }
+        }
+    }
+
+    // Start recording before calling start_loop(), the seed is used for the random number generator
+    pub fn start_recording(&mut self, seed: u64) {
+        srand(seed);
+        self.replay = GMReplayState::Recording(GMReplay::new(seed));
+    }
+    pub fn stop_recording(&mut self) -> Option<GMReplay> {
+        match std::mem::replace(&mut self.replay, GMReplayState::Off) {
+            GMReplayState::Recording(replay) => Some(replay),
+            state => {
+                self.replay = state;
+                None
+            }
+        }
+    }
+    pub fn is_recording(&self) -> bool {
+        matches!(self.replay, GMReplayState::Recording(_))
+    }
+    // The scenes must be in the same state as when the recording was started.
+    // After the last frame the normal input is used again.
+    pub fn start_replay(&mut self, replay: GMReplay) {
+        srand(replay.get_seed());
+        self.replay = GMReplayState::Playing(replay, 0);
+    }
+    pub fn stop_replay(&mut self) {
+        if self.is_replaying() {
+            self.replay = GMReplayState::Off;
+        }
+    }
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.replay, GMReplayState::Playing(_, _))
+    }
+    // Sets the input state for this frame and returns the frame time
+    fn update_input(&mut self, frame_time: f32) -> f32 {
+        // Always poll the gamepads to not miss any connect / disconnect events
+        self.context.gamepad.update();
+
+        if let GMReplayState::Playing(replay, index) = &mut self.replay {
+            if let Some(frame) = replay.get_frame(*index) {
+                self.context.input.set_frame(&frame.input);
+                *index += 1;
+                return frame.dt
+            }
+
+            info!("GMSceneManager::update_input(), replay has finished");
+            self.replay = GMReplayState::Off;
+        }
+
+        self.context.gamepad.apply_to_input(&mut self.context.input);
+        self.context.input.update(self.context.options.get_input_mapping());
+
+        if let GMReplayState::Recording(replay) = &mut self.replay {
+            replay.add_frame(GMReplayFrame {
+                dt: frame_time,
+                input: self.context.input.get_frame(),
+            });
+        }
+
+        frame_time
+    }
     pub async fn start_loop(&mut self) {
         loop {
+            #[cfg(not(target_arch = "wasm32"))]
+            let frame_start = get_time();
+
+            // The language may have been changed in an option menu
+            self.context.resources.get_localization().set_language(self.context.options.get_language());
+
+            let frame_time = self.update_input(get_frame_time());
+            self.clock.advance(frame_time as f64);
+            self.update_frame(frame_time);
             self.draw();
-            self.update();
 
             if self.event() {
                 break;
             }
 
+            // The thread can not be blocked on wasm
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(max_fps) = self.max_fps {
+                let remaining = (1.0 / max_fps as f64) - (get_time() - frame_start);
+
+                if remaining > 0.0 {
+                    thread::sleep(Duration::from_secs_f64(remaining));
+                }
+            }
+
             next_frame().await
         }
     }
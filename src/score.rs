@@ -1,19 +1,35 @@
+use crate::error::GMError;
+use crate::font::GMFont;
+
+use macroquad::file::load_string;
+use nanoserde::{DeJson, SerJson};
+
+use log::{info, error};
+
+use std::collections::HashMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 // TODO:
-// - draw()
-// - update()
-// - event()
-// - add font
-// - add text
-// - add title
-// - add button (OK or BACK)
-// - add load()
-// - add save()
-
-#[derive(Clone, PartialEq, PartialOrd, Eq, Ord)]
+// - show date and extra fields
+
+// Increase this when the file format changes
+pub const GM_SCORE_VERSION: u32 = 1;
+
+fn current_date() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, DeJson, SerJson)]
 pub struct GMScore {
     points: u32,
     name: String,
+    // Seconds since 1970-01-01 (UTC)
+    #[nserde(default)]
+    date: u64,
+    // Game specific data, for ex. level or character
+    #[nserde(default)]
+    extra: Vec<(String, String)>,
 }
 
 impl GMScore {
@@ -21,55 +37,301 @@ impl GMScore {
         Self {
             points,
             name: name.to_string(),
+            date: current_date(),
+            extra: Vec::new(),
+        }
+    }
+    pub fn get_points(&self) -> u32 {
+        self.points
+    }
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn set_name(&mut self, name: &str) {
+        self.name = name.to_string();
+    }
+    pub fn get_date(&self) -> u64 {
+        self.date
+    }
+    pub fn set_date(&mut self, date: u64) {
+        self.date = date;
+    }
+    pub fn set_extra(&mut self, key: &str, value: &str) {
+        match self.extra.iter_mut().find(|(k, _)| k == key) {
+            Some(item) => item.1 = value.to_string(),
+            None => self.extra.push((key.to_string(), value.to_string())),
         }
     }
+    pub fn get_extra(&self, key: &str) -> Option<&str> {
+        self.extra.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GMScoreOrder {
+    // Normal high score table
+    HigherIsBetter,
+    // For ex. time for speedruns
+    LowerIsBetter,
 }
 
+// The best score is always the first entry
 pub struct GMScoreTable {
     scores: Vec<GMScore>,
     max_entries: usize,
+    order: GMScoreOrder,
 }
 
 impl GMScoreTable {
     pub fn new(max_entries: usize) -> Self {
+        Self::new_with_order(max_entries, GMScoreOrder::HigherIsBetter)
+    }
+    pub fn new_with_order(max_entries: usize, order: GMScoreOrder) -> Self {
         Self {
             scores: Vec::with_capacity(max_entries),
             max_entries,
+            order,
         }
     }
     pub fn new_from_vec(scores: Vec<(u32, String)>) -> Self {
-        let mut scores2: Vec<GMScore> = scores.into_iter().map(|(s, n)| GMScore::new(s, &n)).collect();
-        scores2.sort();
+        let mut table = Self::new(scores.len());
 
-        let max_entries = scores2.len();
+        for (points, name) in scores.into_iter() {
+            table.add_score(points, &name);
+        }
 
-        Self {
-            scores: scores2,
-            max_entries,
+        table
+    }
+    pub fn get_order(&self) -> GMScoreOrder {
+        self.order
+    }
+    fn is_better(&self, points: u32, other: u32) -> bool {
+        match self.order {
+            GMScoreOrder::HigherIsBetter => points > other,
+            GMScoreOrder::LowerIsBetter => points < other,
         }
     }
-    pub fn add_score(&mut self, points: u32, name: &str) {
-        if self.enough_points(points) {
-            self.scores.push(GMScore::new(points, name));
-            self.scores.sort();
-            self.scores.truncate(self.max_entries);
+    // Index where a score with the given points would be inserted.
+    // Equal scores are inserted after the existing ones, so the older entry ranks higher.
+    fn position_of(&self, points: u32) -> Option<usize> {
+        let position = self.scores.iter()
+            .position(|s| self.is_better(points, s.points))
+            .unwrap_or(self.scores.len());
+
+        if position < self.max_entries {
+            Some(position)
+        } else {
+            None
         }
     }
+    // Rank (1 is the best) that the given points would get, None if they are not good enough
+    pub fn rank_of(&self, points: u32) -> Option<usize> {
+        self.position_of(points).map(|p| p + 1)
+    }
+    // Returns the index of the new entry, or None if there were not enough points
+    pub fn add_score(&mut self, points: u32, name: &str) -> Option<usize> {
+        self.insert(GMScore::new(points, name))
+    }
+    // Use this to add a score with extra data, returns the index of the new entry
+    pub fn insert(&mut self, score: GMScore) -> Option<usize> {
+        let position = self.position_of(score.points)?;
+
+        self.scores.insert(position, score);
+        self.scores.truncate(self.max_entries);
+
+        Some(position)
+    }
     pub fn enough_points(&self, points: u32) -> bool {
-        if self.scores.len() == self.max_entries {
-            let last = self.scores.last().unwrap();
-            points > last.points
-        } else {
-            true
+        self.position_of(points).is_some()
+    }
+    pub fn get_scores(&self) -> &[GMScore] {
+        &self.scores
+    }
+    // Best score first
+    pub fn iter(&self) -> impl Iterator<Item = &GMScore> {
+        self.scores.iter()
+    }
+    pub fn get_best(&self) -> Option<&GMScore> {
+        self.scores.first()
+    }
+    pub fn get_max_entries(&self) -> usize {
+        self.max_entries
+    }
+    pub fn clear(&mut self) {
+        self.scores.clear();
+    }
+    // Draws rank, name and right aligned points, the highlighted entry is drawn with arrows
+    pub fn draw(&self, font: &GMFont, x: f32, y: f32, width: f32, line_height: f32, highlight: Option<usize>) {
+        let (rank_width, _) = font.get_text_extend("00 ");
+        let (arrow_width, _) = font.get_text_extend("-> ");
+        let mut current_y = y;
+
+        for (i, score) in self.scores.iter().enumerate() {
+            font.draw_text(&format!("{:2}", i + 1), x, current_y);
+            font.draw_text(&score.name, x + rank_width, current_y);
+
+            let points = score.points.to_string();
+            let (points_width, _) = font.get_text_extend(&points);
+            font.draw_text(&points, x + width - points_width, current_y);
+
+            if highlight == Some(i) {
+                font.draw_text("->", x - arrow_width, current_y);
+                font.draw_text("<-", x + width + arrow_width / 3.0, current_y);
+            }
+
+            current_y += line_height;
         }
     }
-    pub fn draw(&self) {
+}
 
+#[derive(Clone, Debug, DeJson, SerJson)]
+pub struct GMFormatScoreTable {
+    name: String,
+    max_entries: usize,
+    #[nserde(default)]
+    lower_is_better: bool,
+    scores: Vec<GMScore>,
+}
+
+#[derive(Clone, Debug, DeJson, SerJson)]
+pub struct GMFormatScoreFile {
+    version: u32,
+    tables: Vec<GMFormatScoreTable>,
+    checksum: String,
+}
+
+// FNV-1a, this is not secure against determined cheaters but detects editing the file by hand
+fn checksum(salt: &str, data: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for byte in salt.bytes().chain(data.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    format!("{:016x}", hash)
+}
+
+// Multiple named score tables, for ex. one per difficulty or game mode
+pub struct GMScoreTables {
+    tables: HashMap<String, GMScoreTable>,
+    // Each game should use its own salt, so that checksums can not be copied from other games
+    salt: String,
+}
+
+impl GMScoreTables {
+    pub fn new() -> Self {
+        Self {
+            tables: HashMap::new(),
+            salt: "GreenMoon2D".to_string(),
+        }
+    }
+    pub fn set_salt(&mut self, salt: &str) {
+        self.salt = salt.to_string();
+    }
+    pub fn add_table(&mut self, name: &str, table: GMScoreTable) {
+        self.tables.insert(name.to_string(), table);
+    }
+    pub fn get_table(&self, name: &str) -> Option<&GMScoreTable> {
+        self.tables.get(name)
+    }
+    pub fn get_table_mut(&mut self, name: &str) -> Option<&mut GMScoreTable> {
+        self.tables.get_mut(name)
+    }
+    pub fn remove_table(&mut self, name: &str) -> Option<GMScoreTable> {
+        self.tables.remove(name)
+    }
+    pub fn get_table_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.tables.keys().map(|n| n.as_str()).collect();
+        names.sort();
+        names
     }
-    pub fn update(&mut self) {
+    fn format_tables(&self) -> Vec<GMFormatScoreTable> {
+        self.get_table_names().iter().map(|name| {
+            let table = &self.tables[*name];
+            GMFormatScoreTable {
+                name: name.to_string(),
+                max_entries: table.max_entries,
+                lower_is_better: table.order == GMScoreOrder::LowerIsBetter,
+                scores: table.scores.clone(),
+            }
+        }).collect()
+    }
+    pub fn to_json(&self) -> String {
+        let tables = self.format_tables();
+        let checksum = checksum(&self.salt, &SerJson::serialize_json(&tables));
+
+        let file = GMFormatScoreFile {
+            version: GM_SCORE_VERSION,
+            tables,
+            checksum,
+        };
 
+        SerJson::serialize_json(&file)
     }
-    pub fn event(&self) -> bool {
-        false
+    // Replaces all tables with the ones from the JSON string
+    pub fn from_json(&mut self, json: &str) -> Result<(), GMError> {
+        let file: GMFormatScoreFile = DeJson::deserialize_json(json)?;
+
+        if file.version != GM_SCORE_VERSION {
+            return Err(GMError::VersionError(file.version))
+        }
+
+        if checksum(&self.salt, &SerJson::serialize_json(&file.tables)) != file.checksum {
+            return Err(GMError::ChecksumError)
+        }
+
+        self.tables.clear();
+
+        for item in file.tables.into_iter() {
+            let order = if item.lower_is_better { GMScoreOrder::LowerIsBetter } else { GMScoreOrder::HigherIsBetter };
+            let mut table = GMScoreTable::new_with_order(item.max_entries, order);
+
+            // Scores are stored best first, so equal scores keep their order
+            for score in item.scores.into_iter() {
+                table.insert(score);
+            }
+
+            self.tables.insert(item.name, table);
+        }
+
+        Ok(())
+    }
+    pub async fn load(&mut self, file_name: &str) -> Result<(), GMError> {
+        info!("Loading score file: '{}'", file_name);
+        let json = load_string(file_name).await?;
+        self.from_json(&json)
+    }
+    // A missing or broken file is not an error, the current tables are kept.
+    // A broken file is renamed (file_name.bak), so that it is not overwritten by the next save().
+    pub async fn load_or_keep(&mut self, file_name: &str) -> bool {
+        match self.load(file_name).await {
+            Ok(()) => true,
+            Err(GMError::FileError(e)) => {
+                info!("GMScoreTables::load_or_keep(), could not read score file '{}': {}", file_name, e);
+                false
+            }
+            Err(e) => {
+                error!("GMScoreTables::load_or_keep(), score file '{}' is broken: {}", file_name, e);
+                let backup = format!("{}.bak", file_name);
+
+                if let Err(e) = fs::rename(file_name, &backup) {
+                    error!("GMScoreTables::load_or_keep(), could not rename score file: {}", e);
+                }
+
+                false
+            }
+        }
+    }
+    pub fn save(&self, file_name: &str) -> Result<(), GMError> {
+        info!("Saving score file: '{}'", file_name);
+        fs::write(file_name, self.to_json())?;
+        Ok(())
+    }
+}
+
+impl Default for GMScoreTables {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,219 @@
+use crate::font::GMFont;
+use crate::text::GMTextStatic;
+use crate::sound::GMSound;
+use crate::menu::GMMenu;
+use crate::menuitem::{GMMenuItem, GMMenuItemStatic, GMMenuItemTextInput};
+use crate::score::GMScore;
+use crate::scene::{GMSceneT, GMSceneResult};
+use crate::context::GMContext;
+use crate::timer::GMTimer;
+use crate::utils::GMValue;
+
+use macroquad::color::colors;
+use macroquad::window::clear_background;
+
+use log::error;
+
+use std::rc::Rc;
+
+// Shows a score table and lets the player enter a name for a new high score.
+// To add a new score send it as a message before changing to this scene:
+//
+// context.send_message("high_score", GMScore::new(points, ""));
+//
+// The name of the score is used as the default name in the name entry.
+pub struct GMScoreScene {
+    // Name of this scene, needed to receive messages
+    name: String,
+    title: String,
+    table_name: String,
+    next_scene: String,
+    font: GMFont,
+    change_sound: Rc<GMSound>,
+    enter_sound: Rc<GMSound>,
+    x: f32,
+    y: f32,
+    width: f32,
+    line_height: f32,
+    max_name_length: usize,
+    // Scores are saved after every new entry
+    file_name: Option<String>,
+    menu: GMMenu,
+    // Score that waits for the name entry
+    new_score: Option<GMScore>,
+    player_name: String,
+    highlight: Option<usize>,
+    blink_on: bool,
+    blink_timer: GMTimer,
+    labels: (String, String, String),
+}
+
+impl GMScoreScene {
+    pub fn new(name: &str, title: &str, table_name: &str, next_scene: &str, font: &GMFont,
+        change_sound: &Rc<GMSound>, enter_sound: &Rc<GMSound>) -> Self {
+        let (_, font_height) = font.get_extend('A');
+        let title_text = GMTextStatic::new_box(title, 0.0, 0.0, font);
+
+        Self {
+            name: name.to_string(),
+            title: title.to_string(),
+            table_name: table_name.to_string(),
+            next_scene: next_scene.to_string(),
+            font: font.clone(),
+            change_sound: change_sound.clone(),
+            enter_sound: enter_sound.clone(),
+            x: 100.0,
+            y: 40.0,
+            width: 400.0,
+            line_height: font_height + 4.0,
+            max_name_length: 10,
+            file_name: None,
+            menu: GMMenu::new_empty(title_text, change_sound, enter_sound),
+            new_score: None,
+            player_name: String::new(),
+            highlight: None,
+            blink_on: true,
+            blink_timer: GMTimer::new_repeat(0.25),
+            labels: ("NAME ".to_string(), "OK".to_string(), "BACK".to_string()),
+        }
+    }
+    pub fn new_box<T>(name: &str, title: &str, table_name: &str, next_scene: &str, font: &GMFont,
+        change_sound: &Rc<GMSound>, enter_sound: &Rc<GMSound>) -> Box<dyn GMSceneT<T>> {
+        Box::new(Self::new(name, title, table_name, next_scene, font, change_sound, enter_sound))
+    }
+    pub fn set_position(&mut self, x: f32, y: f32, width: f32) {
+        self.x = x;
+        self.y = y;
+        self.width = width;
+    }
+    pub fn set_line_height(&mut self, line_height: f32) {
+        self.line_height = line_height;
+    }
+    pub fn set_max_name_length(&mut self, max_name_length: usize) {
+        self.max_name_length = max_name_length;
+    }
+    pub fn set_file_name(&mut self, file_name: &str) {
+        self.file_name = Some(file_name.to_string());
+    }
+    pub fn set_labels(&mut self, name: &str, ok: &str, back: &str) {
+        self.labels = (name.to_string(), ok.to_string(), back.to_string());
+    }
+    fn build_menu(&mut self, num_of_scores: usize) {
+        let title = GMTextStatic::new_box(&self.title, self.x, self.y, &self.font);
+        let mut items: Vec<GMMenuItem> = Vec::new();
+        // Below the title and the score table
+        let mut item_y = self.y + (self.line_height * (num_of_scores as f32 + 3.0));
+
+        if self.new_score.is_some() {
+            items.push(GMMenuItemTextInput::new_static_arrow(&self.labels.0, self.x, item_y,
+                &self.font, &self.player_name, self.max_name_length));
+            item_y += self.line_height * 2.0;
+        }
+
+        items.push(GMMenuItemStatic::new_static_arrow(&self.labels.1, self.x, item_y, &self.font));
+        item_y += self.line_height;
+        items.push(GMMenuItemStatic::new_static_arrow(&self.labels.2, self.x, item_y, &self.font));
+
+        self.menu = GMMenu::new(title, items, &self.change_sound, &self.enter_sound);
+    }
+    fn submit<T>(&mut self, context: &mut GMContext<T>) {
+        if let Some(mut score) = self.new_score.take() {
+            score.set_name(&self.player_name);
+
+            if let Some(table) = context.score_tables.get_table_mut(&self.table_name) {
+                self.highlight = table.insert(score);
+            }
+
+            if let Some(file_name) = &self.file_name {
+                if let Err(e) = context.score_tables.save(file_name) {
+                    error!("GMScoreScene::submit(), could not save scores: {}", e);
+                }
+            }
+        }
+
+        let num_of_scores = self.num_of_scores(context);
+        self.build_menu(num_of_scores);
+    }
+    // Number of lines in the table, including the new entry that waits for the name
+    fn num_of_scores<T>(&self, context: &GMContext<T>) -> usize {
+        match context.score_tables.get_table(&self.table_name) {
+            Some(table) if self.new_score.is_some() => (table.get_scores().len() + 1).min(table.get_max_entries()),
+            Some(table) => table.get_scores().len(),
+            None => 0,
+        }
+    }
+}
+
+impl<T> GMSceneT<T> for GMScoreScene {
+    fn on_enter(&mut self, context: &mut GMContext<T>, _from: Option<&str>) {
+        self.highlight = None;
+        self.new_score = None;
+
+        if let Some(score) = context.take_message::<GMScore>(&self.name) {
+            match context.score_tables.get_table(&self.table_name) {
+                Some(table) => {
+                    if table.enough_points(score.get_points()) {
+                        self.player_name = score.get_name().to_string();
+                        self.new_score = Some(score);
+                    }
+                }
+                None => {
+                    error!("GMScoreScene::on_enter(), unknown score table: '{}'", self.table_name);
+                }
+            }
+        }
+
+        self.blink_timer.start();
+
+        let num_of_scores = self.num_of_scores(context);
+        self.build_menu(num_of_scores);
+    }
+    fn draw(&self, context: &GMContext<T>) {
+        clear_background(colors::BLACK);
+        self.menu.draw();
+
+        if let Some(table) = context.score_tables.get_table(&self.table_name) {
+            let highlight = if self.blink_on { self.highlight } else { None };
+            table.draw(&self.font, self.x, self.y + self.line_height * 2.0, self.width, self.line_height, highlight);
+        }
+    }
+    fn update(&mut self, _context: &mut GMContext<T>, _dt: f32) {
+        if self.blink_timer.finished() {
+            self.blink_on = !self.blink_on;
+        }
+
+        self.menu.update();
+    }
+    fn event(&mut self, context: &mut GMContext<T>) -> GMSceneResult {
+        let entering = self.new_score.is_some();
+        // With name entry the OK and BACK items move down by one
+        let offset = entering as usize;
+
+        match self.menu.event(&context.input) {
+            Some((0, GMValue::String(name))) if entering => {
+                self.player_name = name;
+            }
+            Some((0, GMValue::None)) if entering => {
+                self.submit(context);
+            }
+            Some((i, GMValue::None)) if i == offset => {
+                // OK: store the new entry first
+                if entering {
+                    self.submit(context);
+                } else {
+                    return GMSceneResult::ChangeScene(self.next_scene.clone())
+                }
+            }
+            Some((i, GMValue::None)) if i == offset + 1 => {
+                // BACK: the new entry is dropped
+                self.new_score = None;
+                return GMSceneResult::ChangeScene(self.next_scene.clone())
+            }
+            _ => {
+                // Nothing to do...
+            }
+        }
+
+        GMSceneResult::KeepScene
+    }
+}
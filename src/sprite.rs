@@ -1,29 +1,45 @@
 
 use crate::animation::GMAnimation;
-use crate::spritesheet::GMSpriteSheet;
-use crate::utils::{angle_point, in_rect};
+use crate::spritesheet::{GMSpriteSheet, GMDrawParams};
+use crate::spritestate::GMSpriteStateMachine;
+use crate::collision::{GMShape, GMContact, rotate_point};
+use crate::kinematics::GMKinematics;
+use crate::utils::{angle_point, GMOffscreenMode, GMWorldBounds};
 
-use macroquad::window::{screen_width, screen_height};
+use macroquad::math::Rect;
+use macroquad::color::{Color, colors};
+
+use log::error;
 
 use std::rc::Rc;
 use std::f32::consts;
 
 // TODO:
-// - GMMultiSprite
+// - draw order for the children of GMSpriteMultiple
 
 
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum GMCollisionShape {
+    // Collision box, rotation is ignored
     Rectangle,
+    // Largest circle inside the collision box
     Circle,
+    // Collision box rotated with the sprite
+    RotatedRectangle,
+    // Convex polygon, points are relative to the sprite position and rotated with the sprite
+    Polygon(Vec<(f32, f32)>),
+    // Solid pixels of the current animation frame, rotation, scale and collision box are ignored
+    Mask,
+    // Shapes of all child sprites, only used by GMSpriteMultiple
+    Compound,
 }
 
 pub trait GMSpriteT {
     fn clone_sprite(&self) -> GMSprite;
     fn draw(&self);
     fn draw_at(&self, x: f32, y: f32);
-    fn update(&mut self);
+    fn update(&mut self, dt: f32);
     fn get_extend(&self) -> (f32, f32);
     fn get_state_id(&self) -> u32;
     fn get_active(&self) -> bool;
@@ -41,22 +57,52 @@ pub trait GMSpriteT {
     fn set_mid_y(&mut self, y: f32);
     fn set_vx(&mut self, vx: f32);
     fn set_vy(&mut self, vy: f32);
+    // Acceleration, friction, gravity, ...
+    fn get_kinematics(&self) -> &GMKinematics;
+    fn get_kinematics_mut(&mut self) -> &mut GMKinematics;
     fn set_active(&mut self, active: bool);
     fn set_collision_shape(&mut self, collision_shape: GMCollisionShape);
+    // With a state machine this changes the animation, flip and collision shape
     fn set_state_id(&mut self, state_id: u32);
+    fn set_state_machine(&mut self, state_machine: GMSpriteStateMachine);
+    fn get_state_machine(&self) -> Option<&GMSpriteStateMachine>;
+    fn get_state_machine_mut(&mut self) -> Option<&mut GMSpriteStateMachine>;
     fn set_rotation(&mut self, rotation: f32);
     fn set_rot_speed(&mut self, rot_speed: f32);
     fn rotate_to_point(&mut self, px: f32, py: f32);
+    fn set_scale_xy(&mut self, scale_x: f32, scale_y: f32);
+    fn get_scale(&self) -> (f32, f32);
+    // Pivot relative to the sprite position, None rotates around the mid point
+    fn set_pivot(&mut self, pivot: Option<(f32, f32)>);
+    fn get_pivot(&self) -> Option<(f32, f32)>;
+    // Tint color, white draws the sprite unchanged
+    fn set_color(&mut self, color: Color);
+    fn get_color(&self) -> Color;
+    fn set_alpha(&mut self, alpha: f32);
+    fn get_alpha(&self) -> f32;
+    // Offset and size relative to the sprite position, by default the current animation frame is used
+    fn set_collision_box(&mut self, x: f32, y: f32, width: f32, height: f32);
+    fn reset_collision_box(&mut self);
+    fn get_collision_box(&self) -> Rect;
+    // Collision shape in world coordinates
+    fn get_shape(&self) -> GMShape;
     fn collides_with(&self, other: &GMSprite) -> bool;
+    fn contact_with(&self, other: &GMSprite) -> Option<GMContact>;
+    // Used by is_offscreen(), wrap_around(), bounce() and clamp_to_bounds(), default is the screen
+    fn set_world_bounds(&mut self, world_bounds: GMWorldBounds);
+    fn get_world_bounds(&self) -> GMWorldBounds;
     fn is_offscreen(&self) -> bool;
     fn wrap_around(&mut self);
+    fn bounce(&mut self);
+    fn clamp_to_bounds(&mut self);
     fn animation_finished(&self) -> bool;
     fn flip_x(&mut self, flip_x: bool);
     fn flip_y(&mut self, flip_y: bool);
     fn start_animation(&mut self);
     fn pause_animation(&mut self);
     fn resume_animation(&mut self);
-    fn to_simple(&self) -> GMSpriteSimple;
+    // None if there is nothing to draw, for ex. a GMSpriteMultiple without children
+    fn to_simple(&self) -> Option<GMSpriteSimple>;
 }
 
 pub struct GMSprite {
@@ -75,8 +121,8 @@ impl GMSprite {
     pub fn draw_at(&self, x: f32, y: f32) {
         self.sprite.draw_at(x, y);
     }
-    pub fn update(&mut self) {
-        self.sprite.update();
+    pub fn update(&mut self, dt: f32) {
+        self.sprite.update(dt);
     }
     pub fn get_extend(&self) -> (f32, f32) {
         self.sprite.get_extend()
@@ -129,6 +175,36 @@ impl GMSprite {
     pub fn set_vy(&mut self, vy: f32) {
         self.sprite.set_vy(vy);
     }
+    pub fn get_kinematics(&self) -> &GMKinematics {
+        self.sprite.get_kinematics()
+    }
+    pub fn get_kinematics_mut(&mut self) -> &mut GMKinematics {
+        self.sprite.get_kinematics_mut()
+    }
+    pub fn get_velocity(&self) -> (f32, f32) {
+        self.get_kinematics().get_velocity()
+    }
+    pub fn set_velocity_polar(&mut self, speed: f32, angle: f32) {
+        self.get_kinematics_mut().set_velocity_polar(speed, angle);
+    }
+    pub fn apply_impulse(&mut self, ix: f32, iy: f32) {
+        self.get_kinematics_mut().apply_impulse(ix, iy);
+    }
+    pub fn set_acceleration(&mut self, ax: f32, ay: f32) {
+        self.get_kinematics_mut().set_acceleration(ax, ay);
+    }
+    pub fn set_gravity(&mut self, gravity_x: f32, gravity_y: f32) {
+        self.get_kinematics_mut().set_gravity(gravity_x, gravity_y);
+    }
+    pub fn set_friction(&mut self, friction: f32) {
+        self.get_kinematics_mut().set_friction(friction);
+    }
+    pub fn set_max_speed(&mut self, max_speed: Option<f32>) {
+        self.get_kinematics_mut().set_max_speed(max_speed);
+    }
+    pub fn set_rot_acceleration(&mut self, rot_acceleration: f32) {
+        self.get_kinematics_mut().set_rot_acceleration(rot_acceleration);
+    }
     pub fn set_active(&mut self, active: bool) {
         self.sprite.set_active(active);
     }
@@ -138,6 +214,33 @@ impl GMSprite {
     pub fn set_state_id(&mut self, state_id: u32) {
         self.sprite.set_state_id(state_id);
     }
+    pub fn set_state_machine(&mut self, state_machine: GMSpriteStateMachine) {
+        self.sprite.set_state_machine(state_machine);
+    }
+    pub fn get_state_machine(&self) -> Option<&GMSpriteStateMachine> {
+        self.sprite.get_state_machine()
+    }
+    pub fn get_state_machine_mut(&mut self) -> Option<&mut GMSpriteStateMachine> {
+        self.sprite.get_state_machine_mut()
+    }
+    // Changes to the state with the given name, needs a state machine
+    pub fn set_state(&mut self, name: &str) {
+        let id = self.get_state_machine().and_then(|machine| machine.get_table().get_state_id(name));
+
+        match id {
+            Some(id) => self.set_state_id(id),
+            None => error!("GMSprite::set_state(), unknown state: '{}'", name),
+        }
+    }
+    pub fn get_state_name(&self) -> &str {
+        self.get_state_machine().map(|machine| machine.get_state_name()).unwrap_or("")
+    }
+    // Flags are used by the conditions of the state transitions
+    pub fn set_state_flag(&mut self, flag: &str, value: bool) {
+        if let Some(machine) = self.get_state_machine_mut() {
+            machine.set_flag(flag, value);
+        }
+    }
     pub fn set_rotation(&mut self, rotation: f32) {
         self.sprite.set_rotation(rotation);
     }
@@ -147,15 +250,86 @@ impl GMSprite {
     pub fn rotate_to_point(&mut self, px: f32, py: f32) {
         self.sprite.rotate_to_point(px, py);
     }
+    pub fn set_scale(&mut self, scale: f32) {
+        self.sprite.set_scale_xy(scale, scale);
+    }
+    pub fn set_scale_xy(&mut self, scale_x: f32, scale_y: f32) {
+        self.sprite.set_scale_xy(scale_x, scale_y);
+    }
+    pub fn get_scale(&self) -> (f32, f32) {
+        self.sprite.get_scale()
+    }
+    pub fn set_pivot(&mut self, pivot: Option<(f32, f32)>) {
+        self.sprite.set_pivot(pivot);
+    }
+    pub fn get_pivot(&self) -> Option<(f32, f32)> {
+        self.sprite.get_pivot()
+    }
+    pub fn set_color(&mut self, color: Color) {
+        self.sprite.set_color(color);
+    }
+    pub fn get_color(&self) -> Color {
+        self.sprite.get_color()
+    }
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.sprite.set_alpha(alpha);
+    }
+    pub fn get_alpha(&self) -> f32 {
+        self.sprite.get_alpha()
+    }
+    pub fn set_collision_box(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.sprite.set_collision_box(x, y, width, height);
+    }
+    pub fn reset_collision_box(&mut self) {
+        self.sprite.reset_collision_box();
+    }
+    pub fn get_collision_box(&self) -> Rect {
+        self.sprite.get_collision_box()
+    }
+    pub fn get_shape(&self) -> GMShape {
+        self.sprite.get_shape()
+    }
     pub fn collides_with(&self, other: &GMSprite) -> bool {
         self.sprite.collides_with(other)
     }
+    pub fn contact_with(&self, other: &GMSprite) -> Option<GMContact> {
+        self.sprite.contact_with(other)
+    }
     pub fn is_offscreen(&self) -> bool {
         self.sprite.is_offscreen()
     }
+    pub fn set_world_bounds(&mut self, world_bounds: GMWorldBounds) {
+        self.sprite.set_world_bounds(world_bounds);
+    }
+    pub fn get_world_bounds(&self) -> GMWorldBounds {
+        self.sprite.get_world_bounds()
+    }
     pub fn wrap_around(&mut self) {
         self.sprite.wrap_around()
     }
+    pub fn bounce(&mut self) {
+        self.sprite.bounce()
+    }
+    pub fn clamp_to_bounds(&mut self) {
+        self.sprite.clamp_to_bounds()
+    }
+    pub fn apply_offscreen_mode(&mut self, offscreen_mode: GMOffscreenMode) {
+        use GMOffscreenMode::*;
+
+        match offscreen_mode {
+            Keep => {
+                // Nothing to do, just keep moving the sprite...
+            }
+            Destroy => {
+                if self.is_offscreen() {
+                    self.set_active(false);
+                }
+            }
+            WrapAround => self.wrap_around(),
+            Bounce => self.bounce(),
+            Clamp => self.clamp_to_bounds(),
+        }
+    }
     pub fn animation_finished(&self) -> bool {
         self.sprite.animation_finished()
     }
@@ -174,7 +348,7 @@ impl GMSprite {
     pub fn resume_animation(&mut self) {
         self.sprite.resume_animation();
     }
-    pub fn to_simple(&self) -> GMSpriteSimple {
+    pub fn to_simple(&self) -> Option<GMSpriteSimple> {
         self.sprite.to_simple()
     }
 }
@@ -185,21 +359,157 @@ impl Clone for GMSprite {
     }
 }
 
+// Width and height are the extend of the sprite
+fn outside_bounds(bounds: &Rect, x: f32, y: f32, width: f32, height: f32) -> bool {
+    x + width < bounds.x || x > bounds.right() || y + height < bounds.y || y > bounds.bottom()
+}
+
+// The sprite appears on the other side once it has left the bounds completely
+fn wrap_position(bounds: &Rect, x: &mut f32, y: &mut f32, width: f32, height: f32) {
+    if *x + width < bounds.x {
+        *x += bounds.w + width;
+    } else if *x > bounds.right() {
+        *x -= bounds.w + width;
+    }
+
+    if *y + height < bounds.y {
+        *y += bounds.h + height;
+    } else if *y > bounds.bottom() {
+        *y -= bounds.h + height;
+    }
+}
+
+fn bounce_position(bounds: &Rect, x: &mut f32, y: &mut f32, width: f32, height: f32, kinematics: &mut GMKinematics) {
+    let (mut vx, mut vy) = kinematics.get_velocity();
+
+    if *x < bounds.x {
+        *x = bounds.x;
+        vx = vx.abs();
+    } else if *x + width > bounds.right() {
+        *x = bounds.right() - width;
+        vx = -vx.abs();
+    }
+
+    if *y < bounds.y {
+        *y = bounds.y;
+        vy = vy.abs();
+    } else if *y + height > bounds.bottom() {
+        *y = bounds.bottom() - height;
+        vy = -vy.abs();
+    }
+
+    kinematics.set_velocity(vx, vy);
+}
+
+// Only the velocity towards the border is stopped, so the sprite can still slide along it
+fn clamp_position(bounds: &Rect, x: &mut f32, y: &mut f32, width: f32, height: f32, kinematics: &mut GMKinematics) {
+    let (mut vx, mut vy) = kinematics.get_velocity();
+
+    if *x < bounds.x {
+        *x = bounds.x;
+        vx = vx.max(0.0);
+    } else if *x + width > bounds.right() {
+        *x = bounds.right() - width;
+        vx = vx.min(0.0);
+    }
+
+    if *y < bounds.y {
+        *y = bounds.y;
+        vy = vy.max(0.0);
+    } else if *y + height > bounds.bottom() {
+        *y = bounds.bottom() - height;
+        vy = vy.min(0.0);
+    }
+
+    kinematics.set_velocity(vx, vy);
+}
+
+// Position, size, flip, scale and rotation of a sprite, used to place collision shapes and child sprites
+struct GMFrameTransform {
+    x: f32,
+    y: f32,
+    // Scaled size
+    width: f32,
+    height: f32,
+    flip_x: bool,
+    flip_y: bool,
+    rotation: f32,
+    scale_x: f32,
+    scale_y: f32,
+    // In world coordinates
+    pivot: Option<(f32, f32)>,
+}
+
+impl GMFrameTransform {
+    // Sprites are rotated around the pivot or the mid point of the frame
+    fn get_pivot(&self) -> (f32, f32) {
+        self.pivot.unwrap_or((self.x + (self.width / 2.0), self.y + (self.height / 2.0)))
+    }
+    // Scales and mirrors a point inside the frame
+    fn local_point(&self, x: f32, y: f32) -> (f32, f32) {
+        let x = x * self.scale_x;
+        let y = y * self.scale_y;
+        let x = if self.flip_x { self.width - x } else { x };
+        let y = if self.flip_y { self.height - y } else { y };
+        (x, y)
+    }
+    fn rotate(&self, x: f32, y: f32) -> (f32, f32) {
+        let (pivot_x, pivot_y) = self.get_pivot();
+        rotate_point(x, y, pivot_x, pivot_y, self.rotation)
+    }
+    fn to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        let (x, y) = self.local_point(x, y);
+        self.rotate(self.x + x, self.y + y)
+    }
+    // Collision box is relative to the frame position and not scaled
+    fn to_shape(&self, collision_shape: &GMCollisionShape, local: &Rect) -> GMShape {
+        // Flipping moves the collision box inside the frame
+        let (x1, y1) = self.local_point(local.x, local.y);
+        let (x2, y2) = self.local_point(local.x + local.w, local.y + local.h);
+        let rect = Rect::new(self.x + x1.min(x2), self.y + y1.min(y2), (x2 - x1).abs(), (y2 - y1).abs());
+
+        use GMCollisionShape::*;
+
+        match collision_shape {
+            Circle => {
+                let (mx, my) = self.rotate(rect.x + (rect.w / 2.0), rect.y + (rect.h / 2.0));
+                GMShape::Circle(mx, my, rect.w.min(rect.h) / 2.0)
+            }
+            RotatedRectangle => {
+                let (pivot_x, pivot_y) = self.get_pivot();
+                GMShape::new_rotated_rect(&rect, pivot_x, pivot_y, self.rotation)
+            }
+            Polygon(points) => {
+                GMShape::Polygon(points.iter().map(|(x, y)| self.to_world(*x, *y)).collect())
+            }
+            // Masks and compound shapes need the sprite, see get_shape()
+            Rectangle | Mask | Compound => {
+                GMShape::Rectangle(rect)
+            }
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct GMSpriteSingle {
     sheet: Rc<GMSpriteSheet>,
     animation: GMAnimation,
     x: f32,
     y: f32,
-    vx: f32,
-    vy: f32,
+    kinematics: GMKinematics,
+    world_bounds: GMWorldBounds,
     active: bool,
     collision_shape: GMCollisionShape,
+    collision_box: Option<Rect>,
     state_id: u32,
+    state_machine: Option<GMSpriteStateMachine>,
     flip_x: bool,
     flip_y: bool,
     rotation: f32,
-    rot_speed: f32,
+    scale_x: f32,
+    scale_y: f32,
+    pivot: Option<(f32, f32)>,
+    color: Color,
 }
 
 impl GMSpriteSingle {
@@ -209,22 +519,55 @@ impl GMSpriteSingle {
             animation: animation.clone(),
             x,
             y,
-            vx: 0.0,
-            vy: 0.0,
+            kinematics: GMKinematics::new(),
+            world_bounds: GMWorldBounds::Screen,
             active: true,
             collision_shape: GMCollisionShape::Rectangle,
+            collision_box: None,
             state_id: 0,
+            state_machine: None,
             flip_x: false,
             flip_y: false,
             rotation: 0.0,
-            rot_speed: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            pivot: None,
+            color: colors::WHITE,
         }
     }
     pub fn new_wrapped(sheet: &Rc<GMSpriteSheet>, animation: &GMAnimation, x: f32, y: f32) -> GMSprite {
         let sprite = Self::new(sheet, animation, x, y);
         GMSprite::new(sprite)
     }
+    fn get_transform(&self) -> GMFrameTransform {
+        let (width, height) = self.get_extend();
+
+        GMFrameTransform {
+            x: self.x,
+            y: self.y,
+            width,
+            height,
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            rotation: self.rotation,
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+            pivot: self.pivot.map(|(px, py)| (self.x + px, self.y + py)),
+        }
+    }
+    fn apply_state(&mut self) {
+        if let Some(state) = self.state_machine.as_ref().and_then(|machine| machine.get_current_state()) {
+            self.animation = state.get_animation().clone();
+            self.animation.start();
+            (self.flip_x, self.flip_y) = state.get_flip();
+
+            if let Some(collision_shape) = state.get_collision_shape() {
+                self.collision_shape = collision_shape.clone();
+            }
+        }
+    }
 }
+
 impl GMSpriteT for GMSpriteSingle {
     fn clone_sprite(&self) -> GMSprite {
         let sprite = self.clone();
@@ -234,36 +577,52 @@ impl GMSpriteT for GMSpriteSingle {
         if !self.active {
             return
         }
-        let rect = self.animation.get_rect();
-        self.sheet.draw_ex(&rect, self.x, self.y, self.flip_x, self.flip_y, self.rotation);
+        self.draw_at(self.x, self.y);
     }
     fn draw_at(&self, x: f32, y: f32) {
         if !self.active {
             return
         }
+        let params = GMDrawParams {
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            rotation: self.rotation,
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+            pivot: self.pivot.map(|(px, py)| (x + px, y + py)),
+            color: self.color,
+        };
         let rect = self.animation.get_rect();
-        self.sheet.draw_ex(&rect, x, y, self.flip_x, self.flip_y, self.rotation);
+        self.sheet.draw_params(&rect, x, y, &params);
     }
-    fn update(&mut self) {
+    fn update(&mut self, dt: f32) {
         if !self.active {
             return
         }
 
+        // Velocity and rotation speed are in units per second
         self.animation.next_frame();
-        self.x += self.vx;
-        self.y += self.vy;
-        self.rotation += self.rot_speed;
+
+        if let Some(id) = self.state_machine.as_ref().and_then(|machine| machine.next_state(self.animation.finished())) {
+            self.set_state_id(id);
+        }
+
+        let (dx, dy, rotation) = self.kinematics.update(dt);
+        self.x += dx;
+        self.y += dy;
+        self.rotation += rotation;
 
         if self.rotation > consts::TAU {
             self.rotation -= consts::TAU;
-        } else if self.rotation < consts::TAU {
+        } else if self.rotation < 0.0 {
             self.rotation += consts::TAU;
         }
 
     }
+    // Scaled size of the current animation frame
     fn get_extend(&self) -> (f32, f32) {
         let rect = self.animation.get_rect();
-        (rect.w, rect.h)
+        (rect.w * self.scale_x, rect.h * self.scale_y)
     }
     fn get_state_id(&self) -> u32 {
         self.state_id
@@ -278,16 +637,16 @@ impl GMSpriteT for GMSpriteSingle {
         self.y
     }
     fn get_mid_x(&self) -> f32 {
-        self.x + (self.animation.get_rect().w / 2.0)
+        self.x + (self.get_extend().0 / 2.0)
     }
     fn get_mid_y(&self) -> f32 {
-        self.y + (self.animation.get_rect().h / 2.0)
+        self.y + (self.get_extend().1 / 2.0)
     }
     fn get_rotation(&self) -> f32 {
         self.rotation
     }
     fn get_collision_shape(&self) -> GMCollisionShape {
-        self.collision_shape
+        self.collision_shape.clone()
     }
     fn set_sheet(&mut self, sheet: &Rc<GMSpriteSheet>) {
         self.sheet = sheet.clone();
@@ -302,16 +661,22 @@ impl GMSpriteT for GMSpriteSingle {
         self.y = y;
     }
     fn set_mid_x(&mut self, x: f32) {
-        self.x = x - (self.animation.get_rect().w / 2.0);
+        self.x = x - (self.get_extend().0 / 2.0);
     }
     fn set_mid_y(&mut self, y: f32) {
-        self.y = y - (self.animation.get_rect().h / 2.0);
+        self.y = y - (self.get_extend().1 / 2.0);
     }
     fn set_vx(&mut self, vx: f32) {
-        self.vx = vx;
+        self.kinematics.set_vx(vx);
     }
     fn set_vy(&mut self, vy: f32) {
-        self.vy = vy;
+        self.kinematics.set_vy(vy);
+    }
+    fn get_kinematics(&self) -> &GMKinematics {
+        &self.kinematics
+    }
+    fn get_kinematics_mut(&mut self) -> &mut GMKinematics {
+        &mut self.kinematics
     }
     fn set_active(&mut self, active: bool) {
         self.active = active;
@@ -321,17 +686,85 @@ impl GMSpriteT for GMSpriteSingle {
     }
     fn set_state_id(&mut self, state_id: u32) {
         self.state_id = state_id;
+
+        if let Some(machine) = self.state_machine.as_mut() {
+            if machine.set_current(state_id) {
+                self.apply_state();
+            }
+        }
+    }
+    fn set_state_machine(&mut self, state_machine: GMSpriteStateMachine) {
+        let state_id = state_machine.get_current();
+        self.state_machine = Some(state_machine);
+        self.set_state_id(state_id);
+    }
+    fn get_state_machine(&self) -> Option<&GMSpriteStateMachine> {
+        self.state_machine.as_ref()
+    }
+    fn get_state_machine_mut(&mut self) -> Option<&mut GMSpriteStateMachine> {
+        self.state_machine.as_mut()
     }
     fn set_rotation(&mut self, rotation: f32) {
         self.rotation = rotation;
     }
     fn set_rot_speed(&mut self, rot_speed: f32) {
-        self.rot_speed = rot_speed;
+        self.kinematics.set_rot_speed(rot_speed);
     }
     fn rotate_to_point(&mut self, px: f32, py: f32) {
         let a = angle_point(self.x, self.y, px, py);
         self.set_rotation(a);
     }
+    fn set_scale_xy(&mut self, scale_x: f32, scale_y: f32) {
+        self.scale_x = scale_x;
+        self.scale_y = scale_y;
+    }
+    fn get_scale(&self) -> (f32, f32) {
+        (self.scale_x, self.scale_y)
+    }
+    fn set_pivot(&mut self, pivot: Option<(f32, f32)>) {
+        self.pivot = pivot;
+    }
+    fn get_pivot(&self) -> Option<(f32, f32)> {
+        self.pivot
+    }
+    fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+    fn get_color(&self) -> Color {
+        self.color
+    }
+    fn set_alpha(&mut self, alpha: f32) {
+        self.color.a = alpha;
+    }
+    fn get_alpha(&self) -> f32 {
+        self.color.a
+    }
+    fn set_collision_box(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.collision_box = Some(Rect::new(x, y, width, height));
+    }
+    fn reset_collision_box(&mut self) {
+        self.collision_box = None;
+    }
+    fn get_collision_box(&self) -> Rect {
+        match self.collision_box {
+            Some(rect) => rect,
+            None => {
+                let rect = self.animation.get_rect();
+                Rect::new(0.0, 0.0, rect.w, rect.h)
+            }
+        }
+    }
+    fn get_shape(&self) -> GMShape {
+        match &self.collision_shape {
+            GMCollisionShape::Mask => {
+                let mask = self.sheet.get_mask(&self.animation.get_rect());
+                GMShape::Mask(mask, self.x, self.y, self.flip_x, self.flip_y)
+            }
+            collision_shape => {
+                self.get_transform().to_shape(collision_shape, &self.get_collision_box())
+            }
+        }
+    }
     fn collides_with(&self, other: &GMSprite) -> bool {
         if !self.active {
             return false
@@ -340,88 +773,39 @@ impl GMSpriteT for GMSpriteSingle {
             return false
         }
 
-        let (self_width, self_height) = self.get_extend();
-        let (other_width, other_height) = other.get_extend();
-
-        let sx1 = self.x;
-        let sx2 = self.x + self_width;
-        let sy1 = self.y;
-        let sy2 = self.y + self_height;
-
-        let ox1 = other.get_x();
-        let ox2 = other.get_x() + other_width;
-        let oy1 = other.get_y();
-        let oy2 = other.get_y() + other_height;
-
-        use GMCollisionShape::*;
-
-        match (self.collision_shape, other.get_collision_shape()) {
-            (Rectangle, Rectangle) => {
-                if in_rect(sx1, sx2, sy1, sy2, ox1, oy1) {
-                    return true
-                } else if in_rect(sx1, sx2, sy1, sy2, ox1, oy2) {
-                    return true
-                } else if in_rect(sx1, sx2, sy1, sy2, ox2, oy1) {
-                    return true
-                } else if in_rect(sx1, sx2, sy1, sy2, ox2, oy2) {
-                    return true
-                }
-            }
-            (Rectangle, Circle) => {
-                todo!();
-            }
-            (Circle, Rectangle) => {
-                todo!();
-            }
-            (Circle, Circle) => {
-                let sr = self_width / 2.0;
-                let or = other_width / 2.0;
-
-                let smx = self.x + sr;
-                let smy = self.y + sr;
-                let omx = other.get_x() + or;
-                let omy = other.get_y() + or;
-
-                let dx = smx - omx;
-                let dy = smy - omy;
+        self.get_shape().collides_with(&other.get_shape())
+    }
+    fn contact_with(&self, other: &GMSprite) -> Option<GMContact> {
+        if !self.active {
+            return None
+        }
+        if !other.get_active() {
+            return None
+        }
 
-                let diff = dx.hypot(dy);
-                return diff <= sr + or
-            }
-        };
-        false
+        self.get_shape().contact_with(&other.get_shape())
+    }
+    fn set_world_bounds(&mut self, world_bounds: GMWorldBounds) {
+        self.world_bounds = world_bounds;
+    }
+    fn get_world_bounds(&self) -> GMWorldBounds {
+        self.world_bounds
     }
     fn is_offscreen(&self) -> bool {
         let (width, height) = self.get_extend();
-
-        if self.x + width < 0.0 {
-            return true;
-        } else if self.x > screen_width() {
-            return true;
-        } else if self.y + height < 0.0 {
-            return true;
-        } else if self.y > screen_height() {
-            return true;
-        }
-
-        false
+        outside_bounds(&self.world_bounds.get_rect(), self.x, self.y, width, height)
     }
     fn wrap_around(&mut self) {
         let (width, height) = self.get_extend();
-        let screen_w = screen_width();
-        let screen_h = screen_height();
-        let x2 = self.x + width;
-        let y2 = self.y + height;
-
-        if x2 < 0.0 {
-            self.x = screen_w + x2;
-        } else if self.x > screen_w {
-            self.x = self.x - x2;
-        } else if y2 < 0.0 {
-            self.x = screen_h + y2;
-        } else if self.y > screen_h {
-            self.y = self.y - y2;
-        }
+        wrap_position(&self.world_bounds.get_rect(), &mut self.x, &mut self.y, width, height);
+    }
+    fn bounce(&mut self) {
+        let (width, height) = self.get_extend();
+        bounce_position(&self.world_bounds.get_rect(), &mut self.x, &mut self.y, width, height, &mut self.kinematics);
+    }
+    fn clamp_to_bounds(&mut self) {
+        let (width, height) = self.get_extend();
+        clamp_position(&self.world_bounds.get_rect(), &mut self.x, &mut self.y, width, height, &mut self.kinematics);
     }
     fn animation_finished(&self) -> bool {
         self.animation.finished()
@@ -441,19 +825,536 @@ impl GMSpriteT for GMSpriteSingle {
     fn resume_animation(&mut self) {
         self.animation.resume();
     }
-    fn to_simple(&self) -> GMSpriteSimple {
-        GMSpriteSimple::new(
+    fn to_simple(&self) -> Option<GMSpriteSimple> {
+        let mut simple = GMSpriteSimple::new(
             self.x,
             self.y,
             &self.sheet,
             &self.animation
-        )
+        );
+        simple.set_scale_xy(self.scale_x, self.scale_y);
+        simple.set_color(self.color);
+        simple.flip_x(self.flip_x);
+        simple.flip_y(self.flip_y);
+        Some(simple)
     }
 }
 
+// Part of a GMSpriteMultiple, the offset is the top left corner relative to the parent position
+#[derive(Clone)]
+pub struct GMSpriteChild {
+    sprite: GMSprite,
+    offset_x: f32,
+    offset_y: f32,
+    // Added to the rotation of the parent, for ex. for a turret
+    rotation: f32,
+    flip_x: bool,
+    flip_y: bool,
+}
+
+impl GMSpriteChild {
+    pub fn new(sprite: GMSprite, offset_x: f32, offset_y: f32) -> Self {
+        Self {
+            sprite,
+            offset_x,
+            offset_y,
+            rotation: 0.0,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+}
+
+// Several sprites that move, rotate and flip as one, for ex. a ship with a turret or a boss made of parts.
+// Each child has its own animation. The children are drawn in the order they were added.
 #[derive(Clone)]
 pub struct GMSpriteMultiple {
+    children: Vec<GMSpriteChild>,
+    x: f32,
+    y: f32,
+    kinematics: GMKinematics,
+    world_bounds: GMWorldBounds,
+    active: bool,
+    collision_shape: GMCollisionShape,
+    collision_box: Option<Rect>,
+    state_id: u32,
+    state_machine: Option<GMSpriteStateMachine>,
+    flip_x: bool,
+    flip_y: bool,
+    rotation: f32,
+    scale_x: f32,
+    scale_y: f32,
+    pivot: Option<(f32, f32)>,
+    color: Color,
+}
+
+impl GMSpriteMultiple {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self {
+            children: Vec::new(),
+            x,
+            y,
+            kinematics: GMKinematics::new(),
+            world_bounds: GMWorldBounds::Screen,
+            active: true,
+            collision_shape: GMCollisionShape::Compound,
+            collision_box: None,
+            state_id: 0,
+            state_machine: None,
+            flip_x: false,
+            flip_y: false,
+            rotation: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            pivot: None,
+            color: colors::WHITE,
+        }
+    }
+    pub fn new_wrapped(x: f32, y: f32, children: Vec<GMSpriteChild>) -> GMSprite {
+        let mut sprite = Self::new(x, y);
+
+        for child in children.into_iter() {
+            sprite.add_child(child);
+        }
 
+        GMSprite::new(sprite)
+    }
+    // Returns the index of the new child
+    pub fn add_child(&mut self, mut child: GMSpriteChild) -> usize {
+        // The parent moves the children
+        child.sprite.set_vx(0.0);
+        child.sprite.set_vy(0.0);
+        child.sprite.set_rot_speed(0.0);
+        child.sprite.set_color(self.color);
+        self.children.push(child);
+        self.update_children();
+        self.children.len() - 1
+    }
+    pub fn remove_child(&mut self, index: usize) -> GMSpriteChild {
+        let child = self.children.remove(index);
+        self.update_children();
+        child
+    }
+    pub fn get_child(&self, index: usize) -> &GMSprite {
+        &self.children[index].sprite
+    }
+    // Use this to change the animation of a child, position and rotation are overwritten by the parent
+    pub fn get_child_mut(&mut self, index: usize) -> &mut GMSprite {
+        &mut self.children[index].sprite
+    }
+    pub fn get_num_of_children(&self) -> usize {
+        self.children.len()
+    }
+    pub fn set_child_offset(&mut self, index: usize, offset_x: f32, offset_y: f32) {
+        self.children[index].offset_x = offset_x;
+        self.children[index].offset_y = offset_y;
+        self.update_children();
+    }
+    pub fn set_child_rotation(&mut self, index: usize, rotation: f32) {
+        self.children[index].rotation = rotation;
+        self.update_children();
+    }
+    pub fn get_child_rotation(&self, index: usize) -> f32 {
+        self.children[index].rotation
+    }
+    pub fn set_child_flip(&mut self, index: usize, flip_x: bool, flip_y: bool) {
+        self.children[index].flip_x = flip_x;
+        self.children[index].flip_y = flip_y;
+        self.update_children();
+    }
+    // Scaled box around all children without rotation, relative to the position of the parent.
+    // Children with negative offsets are left of or above the parent position.
+    fn get_local_bounds(&self) -> Rect {
+        self.children.iter().map(|child| {
+            let (width, height) = child.sprite.get_extend();
+            Rect::new(child.offset_x * self.scale_x, child.offset_y * self.scale_y, width, height)
+        })
+        .reduce(|a, b| a.combine_with(b))
+        .unwrap_or_else(|| Rect::new(0.0, 0.0, 0.0, 0.0))
+    }
+    // Same as above in world coordinates
+    fn get_bounds(&self) -> Rect {
+        let bounds = self.get_local_bounds();
+        Rect::new(self.x + bounds.x, self.y + bounds.y, bounds.w, bounds.h)
+    }
+    // Moves the parent, so that the bounds start at the given position
+    fn set_bounds_position(&mut self, x: f32, y: f32) {
+        let bounds = self.get_local_bounds();
+        self.x = x - bounds.x;
+        self.y = y - bounds.y;
+        self.update_children();
+    }
+    // The frame is the box around all children
+    fn get_transform(&self) -> GMFrameTransform {
+        let bounds = self.get_bounds();
+
+        GMFrameTransform {
+            x: bounds.x,
+            y: bounds.y,
+            width: bounds.w,
+            height: bounds.h,
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            rotation: self.rotation,
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+            pivot: self.pivot.map(|(px, py)| (self.x + px, self.y + py)),
+        }
+    }
+    // Moves all children to their place in the world
+    fn update_children(&mut self) {
+        for child in self.children.iter_mut() {
+            child.sprite.set_scale_xy(self.scale_x, self.scale_y);
+        }
+
+        let transform = self.get_transform();
+        let bounds = self.get_local_bounds();
+        // A mirrored sprite rotates the other way round
+        let mirrored = self.flip_x != self.flip_y;
+
+        for child in self.children.iter_mut() {
+            // Offsets are scaled, the size of the child is already scaled.
+            // Position inside the frame, flipping mirrors it on the frame.
+            let (width, height) = child.sprite.get_extend();
+            let x = (child.offset_x * self.scale_x) - bounds.x;
+            let y = (child.offset_y * self.scale_y) - bounds.y;
+            let x = if self.flip_x { bounds.w - x - width } else { x };
+            let y = if self.flip_y { bounds.h - y - height } else { y };
+            let (mid_x, mid_y) = transform.rotate(transform.x + x + (width / 2.0), transform.y + y + (height / 2.0));
+            let rotation = if mirrored { -child.rotation } else { child.rotation };
+
+            child.sprite.set_mid_x(mid_x);
+            child.sprite.set_mid_y(mid_y);
+            child.sprite.set_rotation(self.rotation + rotation);
+            child.sprite.flip_x(child.flip_x != self.flip_x);
+            child.sprite.flip_y(child.flip_y != self.flip_y);
+        }
+    }
+    // The animation of the state is used for the first child
+    fn apply_state(&mut self) {
+        if let Some(state) = self.state_machine.as_ref().and_then(|machine| machine.get_current_state()) {
+            if let Some(child) = self.children.first_mut() {
+                child.sprite.set_animation(state.get_animation());
+                child.sprite.start_animation();
+            }
+
+            (self.flip_x, self.flip_y) = state.get_flip();
+
+            if let Some(collision_shape) = state.get_collision_shape() {
+                self.collision_shape = collision_shape.clone();
+            }
+        }
+
+        self.update_children();
+    }
+}
+
+impl GMSpriteT for GMSpriteMultiple {
+    fn clone_sprite(&self) -> GMSprite {
+        let sprite = self.clone();
+        GMSprite::new(sprite)
+    }
+    fn draw(&self) {
+        if !self.active {
+            return
+        }
+
+        for child in self.children.iter() {
+            child.sprite.draw();
+        }
+    }
+    fn draw_at(&self, x: f32, y: f32) {
+        if !self.active {
+            return
+        }
+
+        let dx = x - self.x;
+        let dy = y - self.y;
+
+        for child in self.children.iter() {
+            child.sprite.draw_at(child.sprite.get_x() + dx, child.sprite.get_y() + dy);
+        }
+    }
+    fn update(&mut self, dt: f32) {
+        if !self.active {
+            return
+        }
+
+        // Velocity and rotation speed are in units per second
+        let (dx, dy, rotation) = self.kinematics.update(dt);
+        self.x += dx;
+        self.y += dy;
+        self.rotation += rotation;
+
+        if self.rotation > consts::TAU {
+            self.rotation -= consts::TAU;
+        } else if self.rotation < 0.0 {
+            self.rotation += consts::TAU;
+        }
+
+        for child in self.children.iter_mut() {
+            child.sprite.update(dt);
+        }
+
+        // Only the first child uses the animation of the state
+        let animation_finished = self.children.first().is_some_and(|child| child.sprite.animation_finished());
+
+        if let Some(id) = self.state_machine.as_ref().and_then(|machine| machine.next_state(animation_finished)) {
+            self.set_state_id(id);
+        }
+
+        // Animation frames may have a different size
+        self.update_children();
+    }
+    // Scaled size of all children without rotation
+    fn get_extend(&self) -> (f32, f32) {
+        let bounds = self.get_local_bounds();
+        (bounds.w, bounds.h)
+    }
+    fn get_state_id(&self) -> u32 {
+        self.state_id
+    }
+    fn get_active(&self) -> bool {
+        self.active
+    }
+    fn get_x(&self) -> f32 {
+        self.x
+    }
+    fn get_y(&self) -> f32 {
+        self.y
+    }
+    fn get_mid_x(&self) -> f32 {
+        self.get_bounds().center().x
+    }
+    fn get_mid_y(&self) -> f32 {
+        self.get_bounds().center().y
+    }
+    fn get_rotation(&self) -> f32 {
+        self.rotation
+    }
+    fn get_collision_shape(&self) -> GMCollisionShape {
+        self.collision_shape.clone()
+    }
+    // Only the first child is changed
+    fn set_sheet(&mut self, sheet: &Rc<GMSpriteSheet>) {
+        if let Some(child) = self.children.first_mut() {
+            child.sprite.set_sheet(sheet);
+        }
+    }
+    // Only the first child is changed, use get_child_mut() for the others
+    fn set_animation(&mut self, animation: &GMAnimation) {
+        if let Some(child) = self.children.first_mut() {
+            child.sprite.set_animation(animation);
+        }
+        self.update_children();
+    }
+    fn set_x(&mut self, x: f32) {
+        self.x = x;
+        self.update_children();
+    }
+    fn set_y(&mut self, y: f32) {
+        self.y = y;
+        self.update_children();
+    }
+    fn set_mid_x(&mut self, x: f32) {
+        let bounds = self.get_bounds();
+        self.set_bounds_position(x - (bounds.w / 2.0), bounds.y);
+    }
+    fn set_mid_y(&mut self, y: f32) {
+        let bounds = self.get_bounds();
+        self.set_bounds_position(bounds.x, y - (bounds.h / 2.0));
+    }
+    fn set_vx(&mut self, vx: f32) {
+        self.kinematics.set_vx(vx);
+    }
+    fn set_vy(&mut self, vy: f32) {
+        self.kinematics.set_vy(vy);
+    }
+    fn get_kinematics(&self) -> &GMKinematics {
+        &self.kinematics
+    }
+    fn get_kinematics_mut(&mut self) -> &mut GMKinematics {
+        &mut self.kinematics
+    }
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+    }
+    fn set_collision_shape(&mut self, collision_shape: GMCollisionShape) {
+        self.collision_shape = collision_shape;
+    }
+    fn set_state_id(&mut self, state_id: u32) {
+        self.state_id = state_id;
+
+        if let Some(machine) = self.state_machine.as_mut() {
+            if machine.set_current(state_id) {
+                self.apply_state();
+            }
+        }
+    }
+    fn set_state_machine(&mut self, state_machine: GMSpriteStateMachine) {
+        let state_id = state_machine.get_current();
+        self.state_machine = Some(state_machine);
+        self.set_state_id(state_id);
+    }
+    fn get_state_machine(&self) -> Option<&GMSpriteStateMachine> {
+        self.state_machine.as_ref()
+    }
+    fn get_state_machine_mut(&mut self) -> Option<&mut GMSpriteStateMachine> {
+        self.state_machine.as_mut()
+    }
+    fn set_rotation(&mut self, rotation: f32) {
+        self.rotation = rotation;
+        self.update_children();
+    }
+    fn set_rot_speed(&mut self, rot_speed: f32) {
+        self.kinematics.set_rot_speed(rot_speed);
+    }
+    fn rotate_to_point(&mut self, px: f32, py: f32) {
+        let a = angle_point(self.x, self.y, px, py);
+        self.set_rotation(a);
+    }
+    // Children are scaled as well
+    fn set_scale_xy(&mut self, scale_x: f32, scale_y: f32) {
+        self.scale_x = scale_x;
+        self.scale_y = scale_y;
+        self.update_children();
+    }
+    fn get_scale(&self) -> (f32, f32) {
+        (self.scale_x, self.scale_y)
+    }
+    fn set_pivot(&mut self, pivot: Option<(f32, f32)>) {
+        self.pivot = pivot;
+        self.update_children();
+    }
+    fn get_pivot(&self) -> Option<(f32, f32)> {
+        self.pivot
+    }
+    // Used for all children
+    fn set_color(&mut self, color: Color) {
+        self.color = color;
+
+        for child in self.children.iter_mut() {
+            child.sprite.set_color(color);
+        }
+    }
+    fn get_color(&self) -> Color {
+        self.color
+    }
+    fn set_alpha(&mut self, alpha: f32) {
+        let mut color = self.color;
+        color.a = alpha;
+        self.set_color(color);
+    }
+    fn get_alpha(&self) -> f32 {
+        self.color.a
+    }
+    // Relative to the top left corner of the box around all children
+    fn set_collision_box(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.collision_box = Some(Rect::new(x, y, width, height));
+    }
+    fn reset_collision_box(&mut self) {
+        self.collision_box = None;
+    }
+    fn get_collision_box(&self) -> Rect {
+        match self.collision_box {
+            Some(rect) => rect,
+            None => {
+                // The collision box is scaled later
+                let (width, height) = self.get_extend();
+                let width = if self.scale_x != 0.0 { width / self.scale_x } else { 0.0 };
+                let height = if self.scale_y != 0.0 { height / self.scale_y } else { 0.0 };
+                Rect::new(0.0, 0.0, width, height)
+            }
+        }
+    }
+    fn get_shape(&self) -> GMShape {
+        match &self.collision_shape {
+            // Each child uses its own collision shape, so masks work as well
+            GMCollisionShape::Compound | GMCollisionShape::Mask => {
+                GMShape::Compound(self.children.iter().map(|child| child.sprite.get_shape()).collect())
+            }
+            collision_shape => {
+                self.get_transform().to_shape(collision_shape, &self.get_collision_box())
+            }
+        }
+    }
+    fn collides_with(&self, other: &GMSprite) -> bool {
+        if !self.active {
+            return false
+        }
+        if !other.get_active() {
+            return false
+        }
+
+        self.get_shape().collides_with(&other.get_shape())
+    }
+    fn contact_with(&self, other: &GMSprite) -> Option<GMContact> {
+        if !self.active {
+            return None
+        }
+        if !other.get_active() {
+            return None
+        }
+
+        self.get_shape().contact_with(&other.get_shape())
+    }
+    fn set_world_bounds(&mut self, world_bounds: GMWorldBounds) {
+        self.world_bounds = world_bounds;
+    }
+    fn get_world_bounds(&self) -> GMWorldBounds {
+        self.world_bounds
+    }
+    fn is_offscreen(&self) -> bool {
+        let bounds = self.get_bounds();
+        outside_bounds(&self.world_bounds.get_rect(), bounds.x, bounds.y, bounds.w, bounds.h)
+    }
+    fn wrap_around(&mut self) {
+        let Rect { mut x, mut y, w, h } = self.get_bounds();
+        wrap_position(&self.world_bounds.get_rect(), &mut x, &mut y, w, h);
+        self.set_bounds_position(x, y);
+    }
+    fn bounce(&mut self) {
+        let Rect { mut x, mut y, w, h } = self.get_bounds();
+        bounce_position(&self.world_bounds.get_rect(), &mut x, &mut y, w, h, &mut self.kinematics);
+        self.set_bounds_position(x, y);
+    }
+    fn clamp_to_bounds(&mut self) {
+        let Rect { mut x, mut y, w, h } = self.get_bounds();
+        clamp_position(&self.world_bounds.get_rect(), &mut x, &mut y, w, h, &mut self.kinematics);
+        self.set_bounds_position(x, y);
+    }
+    // All children have finished their animation
+    fn animation_finished(&self) -> bool {
+        self.children.iter().all(|child| child.sprite.animation_finished())
+    }
+    fn flip_x(&mut self, flip_x: bool) {
+        self.flip_x = flip_x;
+        self.update_children();
+    }
+    fn flip_y(&mut self, flip_y: bool) {
+        self.flip_y = flip_y;
+        self.update_children();
+    }
+    fn start_animation(&mut self) {
+        for child in self.children.iter_mut() {
+            child.sprite.start_animation();
+        }
+    }
+    fn pause_animation(&mut self) {
+        for child in self.children.iter_mut() {
+            child.sprite.pause_animation();
+        }
+    }
+    fn resume_animation(&mut self) {
+        for child in self.children.iter_mut() {
+            child.sprite.resume_animation();
+        }
+    }
+    // Uses the first child
+    fn to_simple(&self) -> Option<GMSpriteSimple> {
+        // The child is already at its place in the world
+        self.children.first()?.sprite.to_simple()
+    }
 }
 
 #[derive(Clone)]
@@ -464,6 +1365,9 @@ pub struct GMSpriteSimple {
     animation: GMAnimation,
     flip_x: bool,
     flip_y: bool,
+    scale_x: f32,
+    scale_y: f32,
+    color: Color,
 }
 
 impl GMSpriteSimple {
@@ -475,11 +1379,22 @@ impl GMSpriteSimple {
             animation: animation.clone(),
             flip_x: false,
             flip_y: false,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            color: colors::WHITE,
         }
     }
     pub fn draw(&self) {
+        let params = GMDrawParams {
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            scale_x: self.scale_x,
+            scale_y: self.scale_y,
+            color: self.color,
+            .. Default::default()
+        };
         let rect = self.animation.get_rect();
-        self.sprite_sheet.draw_ex(&rect, self.x, self.y, self.flip_x, self.flip_y, 0.0);
+        self.sprite_sheet.draw_params(&rect, self.x, self.y, &params);
     }
     pub fn update(&mut self) {
         self.animation.next_frame();
@@ -498,7 +1413,29 @@ impl GMSpriteSimple {
     }
     pub fn get_extend(&self) -> (f32, f32) {
         let rect = self.animation.get_rect();
-        (rect.w, rect.h)
+        (rect.w * self.scale_x, rect.h * self.scale_y)
+    }
+    pub fn set_scale(&mut self, scale: f32) {
+        self.set_scale_xy(scale, scale);
+    }
+    pub fn set_scale_xy(&mut self, scale_x: f32, scale_y: f32) {
+        self.scale_x = scale_x;
+        self.scale_y = scale_y;
+    }
+    pub fn get_scale(&self) -> (f32, f32) {
+        (self.scale_x, self.scale_y)
+    }
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+    pub fn get_color(&self) -> Color {
+        self.color
+    }
+    pub fn set_alpha(&mut self, alpha: f32) {
+        self.color.a = alpha;
+    }
+    pub fn get_alpha(&self) -> f32 {
+        self.color.a
     }
     pub fn set_sprite_sheet(&mut self, sprite_sheet: &Rc<GMSpriteSheet>) {
         self.sprite_sheet = sprite_sheet.clone();
@@ -1,13 +1,51 @@
 use crate::error::GMError;
+use crate::collision::GMMask;
+use crate::animation::GMAnimation;
 
 use macroquad::texture::{Texture2D, draw_texture_ex, load_texture, DrawTextureParams};
-use macroquad::color::colors;
+use macroquad::color::{Color, colors};
+use macroquad::math::vec2;
 use macroquad::math::Rect;
 
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
+// Frame rect: x, y, w, h
+type GMFrameKey = (u32, u32, u32, u32);
+
+// Everything that can be changed when a frame is drawn
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GMDrawParams {
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub rotation: f32,
+    pub scale_x: f32,
+    pub scale_y: f32,
+    // Rotation pivot in world coordinates, None rotates around the mid point
+    pub pivot: Option<(f32, f32)>,
+    // Tint color, the alpha value is used for transparency
+    pub color: Color,
+}
+
+impl Default for GMDrawParams {
+    fn default() -> Self {
+        Self {
+            flip_x: false,
+            flip_y: false,
+            rotation: 0.0,
+            scale_x: 1.0,
+            scale_y: 1.0,
+            pivot: None,
+            color: colors::WHITE,
+        }
+    }
+}
+
 pub struct GMSpriteSheet {
     data: Texture2D,
+    // Built on demand from the pixels of the texture
+    masks: RefCell<HashMap<GMFrameKey, Rc<GMMask>>>,
 }
 
 impl GMSpriteSheet {
@@ -16,6 +54,7 @@ impl GMSpriteSheet {
 
         let sprite_sheet = Self {
             data,
+            masks: RefCell::new(HashMap::new()),
         };
 
         Ok(sprite_sheet)
@@ -28,14 +67,53 @@ impl GMSpriteSheet {
         self.draw_ex(source, x, y, false, false, 0.0)
     }
     pub fn draw_ex(&self, source: &Rect, x: f32, y: f32, flip_x: bool, flip_y: bool, rotation: f32) {
-        let params = DrawTextureParams {
-            source: Some(*source),
+        let params = GMDrawParams {
             flip_x,
             flip_y,
             rotation,
             .. Default::default()
         };
 
-        draw_texture_ex(self.data, x, y, colors::WHITE, params);
+        self.draw_params(source, x, y, &params);
     }
+    pub fn draw_params(&self, source: &Rect, x: f32, y: f32, params: &GMDrawParams) {
+        let texture_params = DrawTextureParams {
+            source: Some(*source),
+            dest_size: Some(vec2(source.w * params.scale_x, source.h * params.scale_y)),
+            flip_x: params.flip_x,
+            flip_y: params.flip_y,
+            rotation: params.rotation,
+            pivot: params.pivot.map(|(px, py)| vec2(px, py)),
+        };
+
+        draw_texture_ex(self.data, x, y, params.color, texture_params);
+    }
+    // Builds the masks for all frames of the animation, so that there is no delay in the game
+    pub fn build_masks(&self, animation: &GMAnimation) {
+        self.build_masks_for(&animation.get_frames());
+    }
+    // Masks that have not been built yet are created here, call build_masks() while loading to avoid this
+    pub fn get_mask(&self, rect: &Rect) -> Rc<GMMask> {
+        self.build_masks_for(&[*rect]);
+        self.masks.borrow()[&frame_key(rect)].clone()
+    }
+    fn build_masks_for(&self, rects: &[Rect]) {
+        let mut masks = self.masks.borrow_mut();
+        let missing: Vec<&Rect> = rects.iter().filter(|rect| !masks.contains_key(&frame_key(rect))).collect();
+
+        if missing.is_empty() {
+            return
+        }
+
+        // Only needed while the masks are built, so it is dropped again afterwards
+        let image = self.data.get_texture_data();
+
+        for rect in missing {
+            masks.insert(frame_key(rect), Rc::new(GMMask::from_image(&image, rect, 0.0)));
+        }
+    }
+}
+
+fn frame_key(rect: &Rect) -> GMFrameKey {
+    (rect.x as u32, rect.y as u32, rect.w as u32, rect.h as u32)
 }
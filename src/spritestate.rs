@@ -0,0 +1,183 @@
+use crate::animation::GMAnimation;
+use crate::sprite::GMCollisionShape;
+
+use log::error;
+
+use std::collections::HashSet;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GMStateCondition {
+    // The animation of the current state has finished
+    AnimationFinished,
+    // Flags are set by the game, for ex. "on_ground" or "hit"
+    Flag(String),
+    NotFlag(String),
+}
+
+impl GMStateCondition {
+    // "animation_finished", "flag_name" or "!flag_name"
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "animation_finished" => Self::AnimationFinished,
+            _ => match name.strip_prefix('!') {
+                Some(flag) => Self::NotFlag(flag.to_string()),
+                None => Self::Flag(name.to_string()),
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct GMSpriteState {
+    name: String,
+    animation: GMAnimation,
+    flip_x: bool,
+    flip_y: bool,
+    // None keeps the current collision shape
+    collision_shape: Option<GMCollisionShape>,
+}
+
+impl GMSpriteState {
+    pub fn new(name: &str, animation: &GMAnimation, flip_x: bool, flip_y: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            animation: animation.clone(),
+            flip_x,
+            flip_y,
+            collision_shape: None,
+        }
+    }
+    pub fn set_collision_shape(&mut self, collision_shape: GMCollisionShape) {
+        self.collision_shape = Some(collision_shape);
+    }
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_animation(&self) -> &GMAnimation {
+        &self.animation
+    }
+    pub fn get_flip(&self) -> (bool, bool) {
+        (self.flip_x, self.flip_y)
+    }
+    pub fn get_collision_shape(&self) -> Option<&GMCollisionShape> {
+        self.collision_shape.as_ref()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GMStateTransition {
+    // None: from any state
+    from: Option<u32>,
+    to: u32,
+    condition: GMStateCondition,
+}
+
+// All states and transitions of a sprite type, shared by all sprites of that type.
+// The state id of a sprite is the index of the state in this table.
+#[derive(Clone, Default)]
+pub struct GMSpriteStateTable {
+    states: Vec<GMSpriteState>,
+    transitions: Vec<GMStateTransition>,
+}
+
+impl GMSpriteStateTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    // Returns the state id, the first state is the start state
+    pub fn add_state(&mut self, state: GMSpriteState) -> u32 {
+        self.states.push(state);
+        (self.states.len() - 1) as u32
+    }
+    // Transitions are checked in the order they were added, from "*" means from any state
+    pub fn add_transition(&mut self, from: &str, to: &str, condition: GMStateCondition) {
+        let from_id = if from == "*" { None } else { self.get_state_id(from) };
+
+        match (from_id, self.get_state_id(to)) {
+            (None, _) if from != "*" => {
+                error!("GMSpriteStateTable::add_transition(), unknown state: '{}'", from);
+            }
+            (_, None) => {
+                error!("GMSpriteStateTable::add_transition(), unknown state: '{}'", to);
+            }
+            (from, Some(to)) => {
+                self.transitions.push(GMStateTransition { from, to, condition });
+            }
+        }
+    }
+    pub fn get_state_id(&self, name: &str) -> Option<u32> {
+        self.states.iter().position(|state| state.name == name).map(|i| i as u32)
+    }
+    pub fn get_state(&self, id: u32) -> Option<&GMSpriteState> {
+        self.states.get(id as usize)
+    }
+    pub fn get_num_of_states(&self) -> usize {
+        self.states.len()
+    }
+}
+
+// Current state and flags of one sprite
+#[derive(Clone)]
+pub struct GMSpriteStateMachine {
+    table: Rc<GMSpriteStateTable>,
+    current: u32,
+    flags: HashSet<String>,
+}
+
+impl GMSpriteStateMachine {
+    pub fn new(table: &Rc<GMSpriteStateTable>) -> Self {
+        Self {
+            table: table.clone(),
+            current: 0,
+            flags: HashSet::new(),
+        }
+    }
+    pub fn get_table(&self) -> &Rc<GMSpriteStateTable> {
+        &self.table
+    }
+    pub fn set_flag(&mut self, flag: &str, value: bool) {
+        if value {
+            self.flags.insert(flag.to_string());
+        } else {
+            self.flags.remove(flag);
+        }
+    }
+    pub fn get_flag(&self, flag: &str) -> bool {
+        self.flags.contains(flag)
+    }
+    pub fn get_current(&self) -> u32 {
+        self.current
+    }
+    pub fn get_current_state(&self) -> Option<&GMSpriteState> {
+        self.table.get_state(self.current)
+    }
+    pub fn get_state_name(&self) -> &str {
+        self.get_current_state().map(|state| state.get_name()).unwrap_or("")
+    }
+    // Returns false for unknown ids
+    pub fn set_current(&mut self, id: u32) -> bool {
+        if self.table.get_state(id).is_some() {
+            self.current = id;
+            true
+        } else {
+            error!("GMSpriteStateMachine::set_current(), unknown state id: {}", id);
+            false
+        }
+    }
+    fn condition_met(&self, condition: &GMStateCondition, animation_finished: bool) -> bool {
+        match condition {
+            GMStateCondition::AnimationFinished => animation_finished,
+            GMStateCondition::Flag(flag) => self.flags.contains(flag),
+            GMStateCondition::NotFlag(flag) => !self.flags.contains(flag),
+        }
+    }
+    // The first transition from the current state whose condition is met, at most one per frame
+    pub fn next_state(&self, animation_finished: bool) -> Option<u32> {
+        self.table.transitions.iter()
+            .filter(|transition| transition.to != self.current)
+            .filter(|transition| transition.from.is_none() || transition.from == Some(self.current))
+            .find(|transition| self.condition_met(&transition.condition, animation_finished))
+            .map(|transition| transition.to)
+    }
+}
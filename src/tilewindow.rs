@@ -1,6 +1,7 @@
 
 use crate::tilemap::GMTileMap;
 use crate::sprite::GMSprite;
+use crate::utils::GMWorldBounds;
 
 use macroquad::camera::{Camera2D, set_camera, set_default_camera};
 use macroquad::texture::{RenderTarget, render_target, draw_texture};
@@ -69,6 +70,10 @@ impl GMTileWindow {
         self.set_buffer_and_camera();
         self.update_buffer();
     }
+    // Size of the whole map, for sprites that move around in it
+    pub fn get_world_bounds(&self) -> GMWorldBounds {
+        GMWorldBounds::new(0.0, 0.0, self.world_size_x, self.world_size_y)
+    }
     pub fn set_world_x(&mut self, x: f32) {
         self.world_x = x;
         self.check_world_x();
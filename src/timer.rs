@@ -0,0 +1,209 @@
+use crate::clock::GMStartTime;
+
+// All times are taken from the active clock (see clock.rs),
+// so timers stop when the scene clock is paused.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GMTimer {
+    duration: f64,
+    start_time: GMStartTime,
+    repeat: bool,
+    active: bool,
+    paused: bool,
+    // Elapsed time when the timer was paused
+    paused_elapsed: f64,
+}
+
+impl GMTimer {
+    pub fn new(duration: f64, repeat: bool) -> Self {
+        Self {
+            duration,
+            start_time: GMStartTime::new(),
+            repeat,
+            active: false,
+            paused: false,
+            paused_elapsed: 0.0,
+        }
+    }
+    // Fires once after the given duration, can also be used as a countdown with get_remaining()
+    pub fn new_once(duration: f64) -> Self {
+        Self::new(duration, false)
+    }
+    // Fires every interval seconds until it is stopped
+    pub fn new_repeat(interval: f64) -> Self {
+        Self::new(interval, true)
+    }
+    pub fn start(&mut self) {
+        self.start_time.restart(0.0);
+        self.active = true;
+        self.paused = false;
+    }
+    pub fn stop(&mut self) {
+        self.active = false;
+        self.paused = false;
+    }
+    pub fn pause(&mut self) {
+        if self.active && !self.paused {
+            self.paused_elapsed = self.get_elapsed();
+            self.paused = true;
+        }
+    }
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.start_time.restart(self.paused_elapsed);
+            self.paused = false;
+        }
+    }
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+    pub fn set_duration(&mut self, duration: f64) {
+        self.duration = duration;
+    }
+    pub fn get_duration(&self) -> f64 {
+        self.duration
+    }
+    pub fn get_elapsed(&self) -> f64 {
+        if !self.active {
+            0.0
+        } else if self.paused {
+            self.paused_elapsed
+        } else {
+            self.start_time.get_elapsed()
+        }
+    }
+    pub fn get_remaining(&self) -> f64 {
+        (self.duration - self.get_elapsed()).max(0.0)
+    }
+    // Value between 0.0 (just started) and 1.0 (time is up)
+    pub fn get_progress(&self) -> f64 {
+        if self.duration <= 0.0 {
+            1.0
+        } else {
+            (self.get_elapsed() / self.duration).min(1.0)
+        }
+    }
+    // Poll this regularly (for ex. in update()).
+    // Returns true once when the time is up, a repeating timer starts the next interval.
+    pub fn finished(&mut self) -> bool {
+        if !self.active || self.paused {
+            return false
+        }
+
+        if self.get_elapsed() < self.duration {
+            return false
+        }
+
+        if self.repeat {
+            // Keep the interval stable, even if the timer is polled too late
+            self.start_time.advance(self.duration);
+        } else {
+            self.active = false;
+        }
+
+        true
+    }
+}
+
+struct GMScheduleEntry {
+    id: usize,
+    timer: GMTimer,
+    callback: Option<Box<dyn FnMut()>>,
+}
+
+pub struct GMScheduler {
+    entries: Vec<GMScheduleEntry>,
+    next_id: usize,
+}
+
+impl GMScheduler {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            next_id: 0,
+        }
+    }
+    fn add_timer(&mut self, mut timer: GMTimer, callback: Option<Box<dyn FnMut()>>) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        timer.start();
+        self.entries.push(GMScheduleEntry { id, timer, callback });
+
+        id
+    }
+    // The returned id is used to cancel the timer and is returned from update() when the timer fires
+    pub fn add_once(&mut self, delay: f64) -> usize {
+        self.add_timer(GMTimer::new_once(delay), None)
+    }
+    pub fn add_repeat(&mut self, interval: f64) -> usize {
+        self.add_timer(GMTimer::new_repeat(interval), None)
+    }
+    pub fn add_once_with<F: 'static + FnMut()>(&mut self, delay: f64, callback: F) -> usize {
+        self.add_timer(GMTimer::new_once(delay), Some(Box::new(callback)))
+    }
+    pub fn add_repeat_with<F: 'static + FnMut()>(&mut self, interval: f64, callback: F) -> usize {
+        self.add_timer(GMTimer::new_repeat(interval), Some(Box::new(callback)))
+    }
+    pub fn cancel(&mut self, id: usize) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|e| e.id != id);
+        len != self.entries.len()
+    }
+    pub fn cancel_all(&mut self) {
+        self.entries.clear();
+    }
+    pub fn pause(&mut self, id: usize) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) {
+            entry.timer.pause();
+        }
+    }
+    pub fn resume(&mut self, id: usize) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.id == id) {
+            entry.timer.resume();
+        }
+    }
+    pub fn pause_all(&mut self) {
+        for entry in self.entries.iter_mut() {
+            entry.timer.pause();
+        }
+    }
+    pub fn resume_all(&mut self) {
+        for entry in self.entries.iter_mut() {
+            entry.timer.resume();
+        }
+    }
+    pub fn get_timer(&self, id: usize) -> Option<&GMTimer> {
+        self.entries.iter().find(|e| e.id == id).map(|e| &e.timer)
+    }
+    pub fn is_scheduled(&self, id: usize) -> bool {
+        self.entries.iter().any(|e| e.id == id)
+    }
+    // Call this once per frame, runs the callbacks of all timers that fired
+    // and returns their ids. One shot timers are removed afterwards.
+    pub fn update(&mut self) -> Vec<usize> {
+        let mut fired = Vec::new();
+
+        for entry in self.entries.iter_mut() {
+            if entry.timer.finished() {
+                if let Some(callback) = &mut entry.callback {
+                    callback();
+                }
+                fired.push(entry.id);
+            }
+        }
+
+        self.entries.retain(|e| e.timer.is_active());
+
+        fired
+    }
+}
+
+impl Default for GMScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
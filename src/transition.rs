@@ -0,0 +1,173 @@
+use crate::utils::GMDirection4;
+use crate::clock::GMClock;
+
+use macroquad::camera::{Camera2D, set_camera, set_default_camera};
+use macroquad::texture::{RenderTarget, render_target, draw_texture, draw_texture_ex, DrawTextureParams};
+use macroquad::shapes::draw_rectangle;
+use macroquad::window::{screen_width, screen_height};
+use macroquad::math::{vec2, Rect};
+use macroquad::color::{Color, colors};
+
+use std::rc::Rc;
+
+// TODO:
+// - add easing for transitions
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GMTransition {
+    // Fade the old scene out to the given color and then fade the new scene in
+    FadeToColor(Color, f64),
+    // The new scene is revealed from left to right
+    WipeHorizontal(f64),
+    // The new scene is revealed from top to bottom
+    WipeVertical(f64),
+    // Both scenes move in the given direction, the new scene pushes the old one out
+    Slide(GMDirection4, f64),
+    // The new scene is blended over the old one
+    CrossFade(f64),
+}
+
+impl GMTransition {
+    pub fn get_duration(&self) -> f64 {
+        use GMTransition::*;
+
+        match self {
+            FadeToColor(_, duration) => *duration,
+            WipeHorizontal(duration) => *duration,
+            WipeVertical(duration) => *duration,
+            Slide(_, duration) => *duration,
+            CrossFade(duration) => *duration,
+        }
+    }
+}
+
+fn buffer_and_camera(width: f32, height: f32) -> (RenderTarget, Camera2D) {
+    let buffer = render_target(width as u32, height as u32);
+    let camera = Camera2D {
+        target: vec2(width / 2.0, height / 2.0),
+        zoom: vec2(2.0 / width, 2.0 / height),
+        render_target: Some(buffer),
+        ..Camera2D::default()
+    };
+
+    (buffer, camera)
+}
+
+pub(crate) struct GMTransitionState {
+    transition: GMTransition,
+    // The game clock, scene clocks may be paused or slowed down
+    clock: Rc<GMClock>,
+    start_time: f64,
+    width: f32,
+    height: f32,
+    old_buffer: RenderTarget,
+    old_camera: Camera2D,
+    new_buffer: RenderTarget,
+    new_camera: Camera2D,
+}
+
+impl GMTransitionState {
+    pub(crate) fn new(transition: GMTransition, clock: &Rc<GMClock>) -> Self {
+        let width = screen_width();
+        let height = screen_height();
+        let (old_buffer, old_camera) = buffer_and_camera(width, height);
+        let (new_buffer, new_camera) = buffer_and_camera(width, height);
+
+        Self {
+            transition,
+            clock: clock.clone(),
+            start_time: clock.get_time(),
+            width,
+            height,
+            old_buffer,
+            old_camera,
+            new_buffer,
+            new_camera,
+        }
+    }
+    fn progress(&self) -> f32 {
+        let duration = self.transition.get_duration();
+
+        if duration <= 0.0 {
+            return 1.0
+        }
+
+        (((self.clock.get_time() - self.start_time) / duration) as f32).min(1.0)
+    }
+    pub(crate) fn finished(&self) -> bool {
+        self.progress() >= 1.0
+    }
+    // All drawing after this call goes into the buffer of the old scene
+    pub(crate) fn begin_old(&self) {
+        set_camera(&self.old_camera);
+    }
+    // All drawing after this call goes into the buffer of the new scene
+    pub(crate) fn begin_new(&self) {
+        set_camera(&self.new_camera);
+    }
+    // Draw both buffers to the screen
+    pub(crate) fn draw(&self) {
+        set_default_camera();
+
+        let t = self.progress();
+        let w = self.width;
+        let h = self.height;
+        let old_texture = self.old_buffer.texture;
+        let new_texture = self.new_buffer.texture;
+
+        use GMTransition::*;
+
+        match self.transition {
+            FadeToColor(color, _) => {
+                let alpha = if t < 0.5 {
+                    draw_texture(old_texture, 0.0, 0.0, colors::WHITE);
+                    t * 2.0
+                } else {
+                    draw_texture(new_texture, 0.0, 0.0, colors::WHITE);
+                    (1.0 - t) * 2.0
+                };
+                draw_rectangle(0.0, 0.0, w, h, Color::new(color.r, color.g, color.b, alpha));
+            }
+            WipeHorizontal(_) => {
+                draw_texture(old_texture, 0.0, 0.0, colors::WHITE);
+                let params = DrawTextureParams {
+                    source: Some(Rect::new(0.0, 0.0, w * t, h)),
+                    .. Default::default()
+                };
+                draw_texture_ex(new_texture, 0.0, 0.0, colors::WHITE, params);
+            }
+            WipeVertical(_) => {
+                draw_texture(old_texture, 0.0, 0.0, colors::WHITE);
+                let params = DrawTextureParams {
+                    source: Some(Rect::new(0.0, 0.0, w, h * t)),
+                    .. Default::default()
+                };
+                draw_texture_ex(new_texture, 0.0, 0.0, colors::WHITE, params);
+            }
+            Slide(direction, _) => {
+                use GMDirection4::*;
+
+                let (dx, dy) = match direction {
+                    Up => (0.0, -h),
+                    Right => (w, 0.0),
+                    Down => (0.0, h),
+                    Left => (-w, 0.0),
+                };
+                draw_texture(old_texture, dx * t, dy * t, colors::WHITE);
+                draw_texture(new_texture, dx * (t - 1.0), dy * (t - 1.0), colors::WHITE);
+            }
+            CrossFade(_) => {
+                draw_texture(old_texture, 0.0, 0.0, colors::WHITE);
+                draw_texture(new_texture, 0.0, 0.0, Color::new(1.0, 1.0, 1.0, t));
+            }
+        }
+    }
+}
+
+// macroquad does not free render targets on its own
+impl Drop for GMTransitionState {
+    fn drop(&mut self) {
+        self.old_buffer.delete();
+        self.new_buffer.delete();
+    }
+}
@@ -0,0 +1,523 @@
+use crate::sprite::GMSprite;
+use crate::text::GMText;
+use crate::menuitem::GMMenuItem;
+use crate::tilewindow::GMTileWindow;
+
+use std::f32::consts;
+
+// TODO:
+// - tween colors
+// - callbacks when a tween has finished
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GMEasing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+}
+
+fn bounce_out(t: f32) -> f32 {
+    let n1 = 7.5625;
+    let d1 = 2.75;
+
+    if t < 1.0 / d1 {
+        n1 * t * t
+    } else if t < 2.0 / d1 {
+        let t = t - (1.5 / d1);
+        n1 * t * t + 0.75
+    } else if t < 2.5 / d1 {
+        let t = t - (2.25 / d1);
+        n1 * t * t + 0.9375
+    } else {
+        let t = t - (2.625 / d1);
+        n1 * t * t + 0.984375
+    }
+}
+
+impl GMEasing {
+    // Maps t from [0.0, 1.0] to the eased value.
+    // The result starts with 0.0 and ends with 1.0 but elastic and back curves overshoot in between.
+    pub fn apply(&self, t: f32) -> f32 {
+        use GMEasing::*;
+
+        let t = t.clamp(0.0, 1.0);
+        // Constants for back and elastic easing
+        let c1 = 1.70158;
+        let c2 = c1 * 1.525;
+        let c3 = c1 + 1.0;
+        let c4 = consts::TAU / 3.0;
+        let c5 = consts::TAU / 4.5;
+
+        match self {
+            Linear => t,
+            QuadIn => t * t,
+            QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            CubicIn => t * t * t,
+            CubicOut => 1.0 - (1.0 - t).powi(3),
+            CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            SineIn => 1.0 - ((t * consts::PI) / 2.0).cos(),
+            SineOut => ((t * consts::PI) / 2.0).sin(),
+            SineInOut => -((consts::PI * t).cos() - 1.0) / 2.0,
+            ElasticIn => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    -(2.0_f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * c4).sin()
+                }
+            }
+            ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * c4).sin() + 1.0
+                }
+            }
+            ElasticInOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else if t < 0.5 {
+                    -(2.0_f32.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * c5).sin()) / 2.0
+                } else {
+                    (2.0_f32.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * c5).sin()) / 2.0 + 1.0
+                }
+            }
+            BounceIn => 1.0 - bounce_out(1.0 - t),
+            BounceOut => bounce_out(t),
+            BounceInOut => {
+                if t < 0.5 {
+                    (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+                } else {
+                    (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+                }
+            }
+            BackIn => c3 * t * t * t - c1 * t * t,
+            BackOut => 1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2),
+            BackInOut => {
+                if t < 0.5 {
+                    ((2.0 * t).powi(2) * ((c2 + 1.0) * 2.0 * t - c2)) / 2.0
+                } else {
+                    ((2.0 * t - 2.0).powi(2) * ((c2 + 1.0) * (t * 2.0 - 2.0) + c2) + 2.0) / 2.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GMTweenProperty {
+    X,
+    Y,
+    Rotation,
+    // Uniform scale
+    Scale,
+    Alpha,
+}
+
+// Everything that can be moved by a tween
+pub trait GMTweenTargetT {
+    fn set_tween_property(&mut self, property: GMTweenProperty, value: f32);
+}
+
+impl GMTweenTargetT for GMSprite {
+    fn set_tween_property(&mut self, property: GMTweenProperty, value: f32) {
+        match property {
+            GMTweenProperty::X => self.set_x(value),
+            GMTweenProperty::Y => self.set_y(value),
+            GMTweenProperty::Rotation => self.set_rotation(value),
+            GMTweenProperty::Scale => self.set_scale(value),
+            GMTweenProperty::Alpha => self.set_alpha(value),
+        }
+    }
+}
+
+impl GMTweenTargetT for GMText {
+    fn set_tween_property(&mut self, property: GMTweenProperty, value: f32) {
+        match property {
+            GMTweenProperty::X => self.set_x(value),
+            GMTweenProperty::Y => self.set_y(value),
+            GMTweenProperty::Rotation | GMTweenProperty::Scale | GMTweenProperty::Alpha => {
+                // Text can not be rotated, scaled or faded
+            }
+        }
+    }
+}
+
+impl GMTweenTargetT for GMMenuItem {
+    fn set_tween_property(&mut self, property: GMTweenProperty, value: f32) {
+        match property {
+            GMTweenProperty::X => self.set_x(value),
+            GMTweenProperty::Y => self.set_y(value),
+            GMTweenProperty::Rotation | GMTweenProperty::Scale | GMTweenProperty::Alpha => {
+                // Menu items can not be rotated, scaled or faded
+            }
+        }
+    }
+}
+
+// Moves the visible part of the world (camera)
+impl GMTweenTargetT for GMTileWindow {
+    fn set_tween_property(&mut self, property: GMTweenProperty, value: f32) {
+        match property {
+            GMTweenProperty::X => self.set_world_x(value),
+            GMTweenProperty::Y => self.set_world_y(value),
+            GMTweenProperty::Rotation | GMTweenProperty::Scale | GMTweenProperty::Alpha => {
+                // Tile window can not be rotated, scaled or faded
+            }
+        }
+    }
+}
+
+pub trait GMTweenT {
+    // dt is the time step in seconds
+    fn update(&mut self, dt: f32);
+    fn apply(&self, target: &mut dyn GMTweenTargetT);
+    fn finished(&self) -> bool;
+    // Time that was left over in the update where the tween finished
+    fn get_overshoot(&self) -> f32;
+    fn reset(&mut self);
+    // impl Clone is not possible because of object safety:
+    // clone() returns Self
+    fn clone_tween(&self) -> GMTween;
+}
+
+pub struct GMTween {
+    tween: Box<dyn GMTweenT>,
+}
+
+impl GMTween {
+    pub fn new<T: 'static + GMTweenT>(tween: T) -> Self {
+        Self {
+            tween: Box::new(tween),
+        }
+    }
+    pub fn update(&mut self, dt: f32) {
+        self.tween.update(dt);
+    }
+    pub fn apply(&self, target: &mut dyn GMTweenTargetT) {
+        self.tween.apply(target);
+    }
+    // Update and apply in one step
+    pub fn update_target(&mut self, dt: f32, target: &mut dyn GMTweenTargetT) {
+        self.tween.update(dt);
+        self.tween.apply(target);
+    }
+    pub fn finished(&self) -> bool {
+        self.tween.finished()
+    }
+    pub fn get_overshoot(&self) -> f32 {
+        self.tween.get_overshoot()
+    }
+    pub fn reset(&mut self) {
+        self.tween.reset();
+    }
+}
+
+impl Clone for GMTween {
+    fn clone(&self) -> Self {
+        self.tween.clone_tween()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GMTweenRepeat {
+    Once,
+    // Number of additional runs
+    Times(u32),
+    Forever,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GMTweenSingle {
+    property: GMTweenProperty,
+    from: f32,
+    to: f32,
+    duration: f32,
+    easing: GMEasing,
+    delay: f32,
+    repeat: GMTweenRepeat,
+    // Every second run goes backwards
+    yoyo: bool,
+    elapsed: f32,
+    runs: u32,
+    forward: bool,
+    finished: bool,
+    overshoot: f32,
+}
+
+impl GMTweenSingle {
+    pub fn new(property: GMTweenProperty, from: f32, to: f32, duration: f32, easing: GMEasing) -> Self {
+        Self {
+            property,
+            from,
+            to,
+            duration,
+            easing,
+            delay: 0.0,
+            repeat: GMTweenRepeat::Once,
+            yoyo: false,
+            elapsed: 0.0,
+            runs: 0,
+            forward: true,
+            finished: false,
+            overshoot: 0.0,
+        }
+    }
+    pub fn new_tween(property: GMTweenProperty, from: f32, to: f32, duration: f32, easing: GMEasing) -> GMTween {
+        GMTween::new(Self::new(property, from, to, duration, easing))
+    }
+    pub fn set_delay(&mut self, delay: f32) {
+        self.delay = delay;
+        self.elapsed = -delay;
+    }
+    pub fn set_repeat(&mut self, repeat: GMTweenRepeat) {
+        self.repeat = repeat;
+    }
+    pub fn set_yoyo(&mut self, yoyo: bool) {
+        self.yoyo = yoyo;
+    }
+    pub fn get_value(&self) -> f32 {
+        let mut t = if self.duration <= 0.0 {
+            1.0
+        } else {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        };
+
+        if !self.forward {
+            t = 1.0 - t;
+        }
+
+        self.from + ((self.to - self.from) * self.easing.apply(t))
+    }
+    fn has_more_runs(&self) -> bool {
+        match self.repeat {
+            GMTweenRepeat::Once => false,
+            GMTweenRepeat::Times(n) => self.runs < n,
+            GMTweenRepeat::Forever => true,
+        }
+    }
+}
+
+impl GMTweenT for GMTweenSingle {
+    fn update(&mut self, dt: f32) {
+        if self.finished {
+            return
+        }
+
+        self.elapsed += dt;
+
+        while self.elapsed >= self.duration {
+            if self.has_more_runs() && self.duration > 0.0 {
+                self.elapsed -= self.duration;
+                self.runs += 1;
+
+                if self.yoyo {
+                    self.forward = !self.forward;
+                }
+            } else {
+                self.overshoot = self.elapsed - self.duration;
+                self.elapsed = self.duration;
+                self.finished = true;
+                break;
+            }
+        }
+    }
+    fn apply(&self, target: &mut dyn GMTweenTargetT) {
+        target.set_tween_property(self.property, self.get_value());
+    }
+    fn finished(&self) -> bool {
+        self.finished
+    }
+    fn get_overshoot(&self) -> f32 {
+        self.overshoot
+    }
+    fn reset(&mut self) {
+        self.elapsed = -self.delay;
+        self.runs = 0;
+        self.forward = true;
+        self.finished = false;
+        self.overshoot = 0.0;
+    }
+    fn clone_tween(&self) -> GMTween {
+        GMTween::new(self.clone())
+    }
+}
+
+// Runs one tween after the other
+#[derive(Clone)]
+pub struct GMTweenSequence {
+    tweens: Vec<GMTween>,
+    current: usize,
+    repeat: GMTweenRepeat,
+    runs: u32,
+    overshoot: f32,
+}
+
+impl GMTweenSequence {
+    pub fn new(tweens: Vec<GMTween>) -> Self {
+        Self {
+            tweens,
+            current: 0,
+            repeat: GMTweenRepeat::Once,
+            runs: 0,
+            overshoot: 0.0,
+        }
+    }
+    pub fn new_tween(tweens: Vec<GMTween>) -> GMTween {
+        GMTween::new(Self::new(tweens))
+    }
+    pub fn set_repeat(&mut self, repeat: GMTweenRepeat) {
+        self.repeat = repeat;
+    }
+    pub fn add_tween(&mut self, tween: GMTween) {
+        self.tweens.push(tween);
+    }
+}
+
+impl GMTweenT for GMTweenSequence {
+    fn update(&mut self, dt: f32) {
+        let mut dt = dt;
+        let mut run_start_dt = dt;
+
+        // The time left over by a finished tween goes to the next one, so the sequence does not drift
+        while self.current < self.tweens.len() {
+            self.tweens[self.current].update(dt);
+
+            if !self.tweens[self.current].finished() {
+                return
+            }
+
+            dt = self.tweens[self.current].get_overshoot();
+            self.current += 1;
+
+            if self.current == self.tweens.len() {
+                let more_runs = match self.repeat {
+                    GMTweenRepeat::Once => false,
+                    GMTweenRepeat::Times(n) => self.runs < n,
+                    GMTweenRepeat::Forever => true,
+                };
+
+                if !more_runs {
+                    self.overshoot = dt;
+                    return
+                }
+
+                self.runs += 1;
+                self.current = 0;
+
+                for tween in self.tweens.iter_mut() {
+                    tween.reset();
+                }
+
+                // A run without any duration would never use up the time
+                if dt >= run_start_dt {
+                    return
+                }
+
+                run_start_dt = dt;
+            }
+        }
+    }
+    fn apply(&self, target: &mut dyn GMTweenTargetT) {
+        // Finished tweens are applied, too, so that their end value is always set
+        let last = self.current.min(self.tweens.len().saturating_sub(1));
+
+        for tween in self.tweens.iter().take(last + 1) {
+            tween.apply(target);
+        }
+    }
+    fn finished(&self) -> bool {
+        self.current >= self.tweens.len()
+    }
+    fn get_overshoot(&self) -> f32 {
+        self.overshoot
+    }
+    fn reset(&mut self) {
+        self.current = 0;
+        self.runs = 0;
+        self.overshoot = 0.0;
+
+        for tween in self.tweens.iter_mut() {
+            tween.reset();
+        }
+    }
+    fn clone_tween(&self) -> GMTween {
+        GMTween::new(self.clone())
+    }
+}
+
+// Runs all tweens at the same time, for ex. one for x and one for y
+#[derive(Clone)]
+pub struct GMTweenParallel {
+    tweens: Vec<GMTween>,
+}
+
+impl GMTweenParallel {
+    pub fn new(tweens: Vec<GMTween>) -> Self {
+        Self {
+            tweens,
+        }
+    }
+    pub fn new_tween(tweens: Vec<GMTween>) -> GMTween {
+        GMTween::new(Self::new(tweens))
+    }
+    pub fn add_tween(&mut self, tween: GMTween) {
+        self.tweens.push(tween);
+    }
+}
+
+impl GMTweenT for GMTweenParallel {
+    fn update(&mut self, dt: f32) {
+        for tween in self.tweens.iter_mut() {
+            tween.update(dt);
+        }
+    }
+    fn apply(&self, target: &mut dyn GMTweenTargetT) {
+        for tween in self.tweens.iter() {
+            tween.apply(target);
+        }
+    }
+    fn finished(&self) -> bool {
+        self.tweens.iter().all(|tween| tween.finished())
+    }
+    // The group finishes with the tween that finished last
+    fn get_overshoot(&self) -> f32 {
+        self.tweens.iter().map(|tween| tween.get_overshoot()).reduce(f32::min).unwrap_or(0.0)
+    }
+    fn reset(&mut self) {
+        for tween in self.tweens.iter_mut() {
+            tween.reset();
+        }
+    }
+    fn clone_tween(&self) -> GMTween {
+        GMTween::new(self.clone())
+    }
+}
@@ -1,3 +1,7 @@
+use macroquad::math::Rect;
+use macroquad::window::{screen_width, screen_height};
+use nanoserde::{DeJson, SerJson};
+
 use std::f32::consts;
 use std::any::Any;
 pub struct GMKeyValue<'a> {
@@ -10,9 +14,34 @@ pub enum GMOffscreenMode {
     Keep,
     Destroy,
     WrapAround,
+    // Reverses the velocity at the border
+    Bounce,
+    // Stops at the border
+    Clamp,
+}
+
+// Area in which sprites can move, used for GMOffscreenMode
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum GMWorldBounds {
+    // The current size of the window
+    #[default]
+    Screen,
+    Rect(Rect),
+}
+
+impl GMWorldBounds {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self::Rect(Rect::new(x, y, width, height))
+    }
+    pub fn get_rect(&self) -> Rect {
+        match self {
+            Self::Screen => Rect::new(0.0, 0.0, screen_width(), screen_height()),
+            Self::Rect(rect) => *rect,
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, DeJson, SerJson)]
 pub enum GMValue {
     None,
     Bool(bool),
@@ -21,6 +50,7 @@ pub enum GMValue {
     String(String),
 }
 
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum GMDirection4 {
     Up,
     Right,